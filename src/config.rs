@@ -1,21 +1,114 @@
 use crate::error::{RaystError, Result};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SceneConfig {
     pub camera: CameraConfig,
     pub render: RenderConfig,
     pub objects: Vec<ObjectConfig>,
+    #[serde(default)]
     pub lights: Vec<LightConfig>,
     pub background: BackgroundConfig,
+    #[serde(default)]
+    pub materials: HashMap<String, MaterialConfig>,
+    #[serde(default)]
+    pub material_libraries: Vec<String>,
+    #[serde(default)]
+    pub groups: HashMap<String, GroupConfig>,
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+// A scene file as written, before its includes are merged in. Every table is
+// optional since parts such as a lighting rig only define some of them.
+#[derive(Debug, Default, Deserialize)]
+struct SceneFileConfig {
+    #[serde(default)]
+    include: Vec<String>,
+    camera: Option<CameraConfig>,
+    render: Option<RenderConfig>,
+    #[serde(default)]
+    objects: Vec<ObjectConfig>,
+    #[serde(default)]
+    lights: Vec<LightConfig>,
+    background: Option<BackgroundConfig>,
+    #[serde(default)]
+    materials: HashMap<String, MaterialConfig>,
+    #[serde(default)]
+    material_libraries: Vec<String>,
+    #[serde(default)]
+    groups: HashMap<String, GroupConfig>,
+}
+
+impl SceneFileConfig {
+    // `other` is merged after `self`: its tables replace ours, its objects
+    // and lights are appended, and its materials win on name collisions
+    fn merge(&mut self, other: SceneFileConfig) {
+        self.camera = other.camera.or(self.camera.take());
+        self.render = other.render.or(self.render.take());
+        self.background = other.background.or(self.background.take());
+        self.objects.extend(other.objects);
+        self.lights.extend(other.lights);
+        self.materials.extend(other.materials);
+        self.material_libraries.extend(other.material_libraries);
+        self.groups.extend(other.groups);
+    }
+
+    // Makes relative file references of an included file relative to the
+    // top-level scene instead
+    fn rebase_paths(&mut self, directory: &Path) {
+        let rebase = |file: &mut String| {
+            *file = directory.join(&*file).to_string_lossy().into_owned();
+        };
+
+        for object in &mut self.objects {
+            object.rebase_paths(directory);
+        }
+        for light in &mut self.lights {
+            if let Some(profile) = &mut light.ies_profile {
+                rebase(profile);
+            }
+        }
+        if let Some(environment) = self
+            .background
+            .as_mut()
+            .and_then(|background| background.environment.as_mut())
+        {
+            rebase(environment);
+        }
+        for material in self.materials.values_mut() {
+            material.rebase_paths(directory);
+        }
+        for object in self
+            .groups
+            .values_mut()
+            .flat_map(|group| &mut group.objects)
+        {
+            object.rebase_paths(directory);
+        }
+        self.material_libraries.iter_mut().for_each(rebase);
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CameraConfig {
     pub position: [f32; 3],
-    pub direction: [f32; 3],
+    pub direction: Option<[f32; 3]>,
+    pub look_at: Option<[f32; 3]>,
+    pub up: Option<[f32; 3]>,
+    pub roll: Option<f32>,
     pub fov: f32,
+    pub projection: Option<String>,
+    pub ortho_width: Option<f32>,
+    pub aperture: Option<f32>,
+    pub focus_distance: Option<f32>,
+    pub focus_point: Option<[f32; 3]>,
+    pub blades: Option<u32>,
+    pub blade_rotation: Option<f32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -25,6 +118,46 @@ pub struct RenderConfig {
     pub max_depth: u32,
     pub antialiasing: u32,
     pub output_file: String,
+    pub integrator: Option<String>,
+    pub depth_range: Option<f32>,
+    pub tone_mapping: Option<String>,
+    pub white_point: Option<f32>,
+    pub exposure: Option<f32>,
+    pub aovs: Option<AovConfig>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AovConfig {
+    pub depth: Option<bool>,
+    pub normal: Option<bool>,
+    pub albedo: Option<bool>,
+    pub object_id: Option<bool>,
+    pub material_id: Option<bool>,
+    pub position: Option<bool>,
+    pub multilayer: Option<bool>,
+}
+
+impl AovConfig {
+    pub fn enabled(&self) -> Vec<crate::renderer::Aov> {
+        use crate::renderer::Aov;
+
+        [
+            (self.depth, Aov::Depth),
+            (self.normal, Aov::Normal),
+            (self.albedo, Aov::Albedo),
+            (self.object_id, Aov::ObjectId),
+            (self.material_id, Aov::MaterialId),
+            (self.position, Aov::Position),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| enabled.unwrap_or(false))
+        .map(|(_, aov)| aov)
+        .collect()
+    }
+
+    pub fn is_multilayer(&self) -> bool {
+        self.multilayer.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -34,20 +167,20 @@ pub enum ObjectConfig {
     Sphere {
         position: [f32; 3],
         radius: f32,
-        material: MaterialConfig,
+        material: MaterialRef,
     },
     #[serde(rename = "plane")]
     Plane {
         position: [f32; 3],
         normal: [f32; 3],
-        material: MaterialConfig,
+        material: MaterialRef,
     },
     #[serde(rename = "cube")]
     Cube {
         position: [f32; 3],
         size: f32,
         rotation: Option<[f32; 3]>,
-        material: MaterialConfig,
+        material: MaterialRef,
     },
     #[serde(rename = "pyramid")]
     Pyramid {
@@ -55,8 +188,137 @@ pub enum ObjectConfig {
         base_size: f32,
         height: f32,
         rotation: Option<[f32; 3]>,
-        material: MaterialConfig,
+        material: MaterialRef,
+    },
+    #[serde(rename = "cylinder")]
+    Cylinder {
+        position: [f32; 3],
+        radius: f32,
+        height: f32,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+    },
+    #[serde(rename = "cone")]
+    Cone {
+        position: [f32; 3],
+        radius: f32,
+        height: f32,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+    },
+    #[serde(rename = "disk")]
+    Disk {
+        position: [f32; 3],
+        radius: f32,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+    },
+    #[serde(rename = "torus")]
+    Torus {
+        position: [f32; 3],
+        major_radius: f32,
+        minor_radius: f32,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+    },
+    #[serde(rename = "capsule")]
+    Capsule {
+        position: [f32; 3],
+        radius: f32,
+        height: f32,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+    },
+    #[serde(rename = "mesh")]
+    Mesh {
+        file: String,
+        position: [f32; 3],
+        scale: Option<[f32; 3]>,
+        rotation: Option<[f32; 3]>,
+        material: MaterialRef,
+        materials: Option<HashMap<String, MaterialRef>>,
+        crease_angle: Option<f32>,
+    },
+    #[serde(rename = "group")]
+    Group {
+        objects: Vec<ObjectConfig>,
+        #[serde(flatten)]
+        transform: TransformConfig,
+    },
+    #[serde(rename = "csg")]
+    Csg {
+        operation: String,
+        objects: Vec<ObjectConfig>,
     },
+    #[serde(rename = "instance")]
+    Instance {
+        group: String,
+        #[serde(flatten)]
+        transform: TransformConfig,
+    },
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TransformConfig {
+    pub position: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 3]>,
+    pub quaternion: Option<[f32; 4]>,
+    pub scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GroupConfig {
+    pub objects: Vec<ObjectConfig>,
+}
+
+// An object's material, either a table of its own or the name of an entry
+// in `[materials]`
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum MaterialRef {
+    Named(String),
+    Inline(Box<MaterialConfig>),
+}
+
+impl<'de> Deserialize<'de> for MaterialRef {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct MaterialRefVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MaterialRefVisitor {
+            type Value = MaterialRef;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a material name or a material table")
+            }
+
+            fn visit_str<E: serde::de::Error>(
+                self,
+                name: &str,
+            ) -> std::result::Result<MaterialRef, E> {
+                Ok(MaterialRef::Named(name.to_string()))
+            }
+
+            // Deserialize the table directly so that errors point at the
+            // offending field
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> std::result::Result<MaterialRef, A::Error> {
+                MaterialConfig::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+                    .map(|config| MaterialRef::Inline(Box::new(config)))
+            }
+        }
+
+        deserializer.deserialize_any(MaterialRefVisitor)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MaterialLibraryConfig {
+    #[serde(default)]
+    materials: HashMap<String, MaterialConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -68,29 +330,280 @@ pub struct MaterialConfig {
     pub emission: Option<[f32; 3]>,
     pub transparency: Option<f32>,
     pub ior: Option<f32>,
+    pub absorption: Option<f32>,
     pub ao: Option<f32>,
+    pub albedo_texture: Option<TextureConfig>,
+    pub metallic_texture: Option<TextureConfig>,
+    pub roughness_texture: Option<TextureConfig>,
+    pub ao_texture: Option<TextureConfig>,
+    pub normal_map: Option<TextureConfig>,
+    pub bump_map: Option<TextureConfig>,
+    pub bump_strength: Option<f32>,
+}
+
+impl ObjectConfig {
+    fn rebase_paths(&mut self, directory: &Path) {
+        let material = match self {
+            ObjectConfig::Sphere { material, .. }
+            | ObjectConfig::Plane { material, .. }
+            | ObjectConfig::Cube { material, .. }
+            | ObjectConfig::Pyramid { material, .. }
+            | ObjectConfig::Cylinder { material, .. }
+            | ObjectConfig::Cone { material, .. }
+            | ObjectConfig::Disk { material, .. }
+            | ObjectConfig::Torus { material, .. }
+            | ObjectConfig::Capsule { material, .. } => material,
+            ObjectConfig::Group { objects, .. } | ObjectConfig::Csg { objects, .. } => {
+                for object in objects {
+                    object.rebase_paths(directory);
+                }
+                return;
+            }
+            ObjectConfig::Instance { .. } => return,
+            ObjectConfig::Mesh {
+                file,
+                material,
+                materials,
+                ..
+            } => {
+                *file = directory.join(&*file).to_string_lossy().into_owned();
+                for group_material in materials.iter_mut().flat_map(|map| map.values_mut()) {
+                    group_material.rebase_paths(directory);
+                }
+                material
+            }
+        };
+        material.rebase_paths(directory);
+    }
+}
+
+impl MaterialRef {
+    fn rebase_paths(&mut self, directory: &Path) {
+        if let MaterialRef::Inline(config) = self {
+            config.rebase_paths(directory);
+        }
+    }
+}
+
+impl MaterialConfig {
+    fn rebase_paths(&mut self, directory: &Path) {
+        for texture in [
+            &mut self.albedo_texture,
+            &mut self.metallic_texture,
+            &mut self.roughness_texture,
+            &mut self.ao_texture,
+            &mut self.normal_map,
+            &mut self.bump_map,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let TextureConfig::Image { file, .. } = texture {
+                *file = directory.join(&*file).to_string_lossy().into_owned();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "texture_type")]
+pub enum TextureConfig {
+    #[serde(rename = "image")]
+    Image {
+        file: String,
+        wrap: Option<String>,
+        scale: Option<[f32; 2]>,
+        linear: Option<bool>,
+    },
+    #[serde(rename = "checker")]
+    Checker {
+        even: [f32; 3],
+        odd: [f32; 3],
+        scale: Option<f32>,
+    },
+    #[serde(rename = "gradient")]
+    Gradient {
+        start: [f32; 3],
+        end: [f32; 3],
+        direction: Option<[f32; 2]>,
+        wrap: Option<String>,
+    },
+    #[serde(rename = "noise")]
+    Noise {
+        low: [f32; 3],
+        high: [f32; 3],
+        scale: Option<f32>,
+        octaves: Option<u32>,
+        seed: Option<u64>,
+    },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LightConfig {
-    pub position: [f32; 3],
+    pub light_type: Option<String>,
+    pub position: Option<[f32; 3]>,
+    pub direction: Option<[f32; 3]>,
+    pub angular_diameter: Option<f32>,
+    pub inner_angle: Option<f32>,
+    pub outer_angle: Option<f32>,
+    pub ies_profile: Option<String>,
+    pub ies_rotation: Option<f32>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub radius: Option<f32>,
     pub color: [u8; 3],
     pub intensity: f32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl LightConfig {
+    // `color` is written 0-255 for every light type and scaled to 0-1 here,
+    // so `intensity` means the same thing throughout
+    pub fn linear_color(&self) -> Vec3 {
+        Vec3::from_array(self.color.map(|c| c as f32 / 255.0))
+    }
+
+    pub fn is_area(&self) -> bool {
+        matches!(self.light_type.as_deref(), Some("rect" | "disk" | "sphere"))
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct BackgroundConfig {
-    pub color: [u8; 3],
+    pub color: Option<[u8; 3]>,
+    pub environment: Option<String>,
+    pub rotation: Option<f32>,
+    pub intensity: Option<f32>,
+    pub sky: Option<SkyConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SkyConfig {
+    pub sun_elevation: f32,
+    pub sun_azimuth: Option<f32>,
+    pub turbidity: Option<f32>,
+    pub resolution: Option<[usize; 2]>,
 }
 
 impl SceneConfig {
     pub fn load_from_file(path: &str) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let config: SceneConfig = toml::from_str(&content)
-            .map_err(|e| RaystError::InvalidInput(format!("Failed to load scene config: {}", e)))?;
+        let file = Self::load_scene_file(Path::new(path), Path::new(""), &mut Vec::new())?;
+        let missing = |table: &str| {
+            RaystError::InvalidInput(format!(
+                "Failed to load scene config: no [{}] table in {} or its includes",
+                table, path
+            ))
+        };
+
+        let mut config = SceneConfig {
+            camera: file.camera.ok_or_else(|| missing("camera"))?,
+            render: file.render.ok_or_else(|| missing("render"))?,
+            objects: file.objects,
+            lights: file.lights,
+            background: file.background.unwrap_or_default(),
+            materials: file.materials,
+            material_libraries: file.material_libraries,
+            groups: file.groups,
+            base_dir: Path::new(path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        config.load_material_libraries()?;
         Ok(config)
     }
 
+    // Loads a scene file with its includes merged in depth first, in listed
+    // order, followed by the file's own content. `directory` is the file's
+    // location relative to the top-level scene and `stack` the chain of files
+    // currently being included, used to detect cycles.
+    fn load_scene_file(
+        path: &Path,
+        directory: &Path,
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<SceneFileConfig> {
+        let error = |msg: String| {
+            RaystError::InvalidInput(format!(
+                "Failed to load scene config {}: {}",
+                path.display(),
+                msg
+            ))
+        };
+
+        let canonical = std::fs::canonicalize(path).map_err(|e| error(e.to_string()))?;
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain([&canonical])
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(RaystError::InvalidInput(format!(
+                "Scene include cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let mut file: SceneFileConfig =
+            toml::from_str(&content).map_err(|e| error(e.to_string()))?;
+        file.rebase_paths(directory);
+
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let mut merged = SceneFileConfig::default();
+
+        stack.push(canonical);
+        for include in std::mem::take(&mut file.include) {
+            let include_directory = directory
+                .join(&include)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            merged.merge(Self::load_scene_file(
+                &parent.join(&include),
+                &include_directory,
+                stack,
+            )?);
+        }
+        stack.pop();
+
+        merged.merge(file);
+        Ok(merged)
+    }
+
+    // Merges library materials into `materials`. Names defined in the scene
+    // win over libraries, and later libraries win over earlier ones.
+    fn load_material_libraries(&mut self) -> Result<()> {
+        for library in self.material_libraries.iter().rev() {
+            let path = self.resolve_path(library);
+            let error = |msg: String| {
+                RaystError::InvalidInput(format!(
+                    "Failed to load material library {}: {}",
+                    path.display(),
+                    msg
+                ))
+            };
+
+            let content = std::fs::read_to_string(&path).map_err(|e| error(e.to_string()))?;
+            let config: MaterialLibraryConfig =
+                toml::from_str(&content).map_err(|e| error(e.to_string()))?;
+            let directory = std::fs::canonicalize(&path)
+                .map_err(|e| error(e.to_string()))?
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            for (name, mut material) in config.materials {
+                if let std::collections::hash_map::Entry::Vacant(entry) = self.materials.entry(name)
+                {
+                    // Texture files are relative to the library, not the scene
+                    material.rebase_paths(&directory);
+                    entry.insert(material);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn save_to_file(&self, path: &str) -> Result<()> {
         let content = toml::to_string_pretty(self)
             .map_err(|e| RaystError::InvalidInput(format!("Failed to save scene config: {}", e)))?;
@@ -98,16 +611,549 @@ impl SceneConfig {
         Ok(())
     }
 
-    pub fn to_camera(&self) -> crate::scene::Camera {
-        crate::scene::Camera::new(
-            Vec3::from_array(self.camera.position),
-            Vec3::from_array(self.camera.direction),
+    pub fn resolve_path(&self, path: &str) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    pub fn to_camera(&self) -> Result<crate::scene::Camera> {
+        let position = Vec3::from_array(self.camera.position);
+
+        let direction = match (self.camera.direction, self.camera.look_at) {
+            (Some(direction), None) => Vec3::from_array(direction),
+            (None, Some(target)) => {
+                let direction = Vec3::from_array(target) - position;
+                if direction.length_squared() < 1e-12 {
+                    return Err(RaystError::InvalidInput(String::from(
+                        "Camera look_at must differ from the camera position",
+                    )));
+                }
+                direction
+            }
+            (Some(_), Some(_)) => {
+                return Err(RaystError::InvalidInput(String::from(
+                    "Camera accepts either direction or look_at, not both",
+                )));
+            }
+            (None, None) => {
+                return Err(RaystError::InvalidInput(String::from(
+                    "Camera needs a direction or a look_at target",
+                )));
+            }
+        };
+
+        let up = self
+            .camera
+            .up
+            .map(Vec3::from_array)
+            .unwrap_or_else(|| crate::scene::Camera::default_up(direction));
+
+        let mut camera = crate::scene::Camera::oriented(
+            position,
+            direction,
+            up,
+            self.camera.roll.unwrap_or(0.0),
             self.camera.fov,
+        )?;
+
+        camera.projection = self.to_projection()?;
+        camera.aperture = self.camera.aperture.unwrap_or(0.0);
+        if camera.aperture < 0.0 || camera.aperture.is_nan() {
+            return Err(RaystError::InvalidInput(format!(
+                "Camera aperture must not be negative, got {}",
+                camera.aperture
+            )));
+        }
+
+        if camera.aperture > 0.0 && !camera.projection.supports_depth_of_field() {
+            return Err(RaystError::InvalidInput(String::from(
+                "Camera aperture is only supported with perspective and orthographic projections",
+            )));
+        }
+        camera.blades = self.camera.blades.unwrap_or(0);
+        camera.blade_rotation = self.camera.blade_rotation.unwrap_or(0.0);
+
+        match (self.camera.focus_point, self.camera.focus_distance) {
+            (Some(point), _) => camera.focus_on(Vec3::from_array(point)),
+            (None, Some(distance)) if distance > 0.0 => camera.focus_distance = distance,
+            (None, Some(distance)) => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Camera focus_distance must be positive, got {}",
+                    distance
+                )));
+            }
+            (None, None) if camera.aperture > 0.0 => {
+                return Err(RaystError::InvalidInput(String::from(
+                    "Camera aperture requires a focus_distance or focus_point",
+                )));
+            }
+            (None, None) => {}
+        }
+
+        Ok(camera)
+    }
+
+    pub fn to_light(&self, light: &LightConfig) -> Result<Box<dyn crate::lighting::Light>> {
+        use crate::lighting::*;
+        use crate::loaders::IesProfile;
+
+        let color = light.linear_color();
+        let light_type = light.light_type.as_deref().unwrap_or("point");
+
+        let position = || {
+            light.position.map(Vec3::from_array).ok_or_else(|| {
+                RaystError::InvalidInput(format!("{} light requires a position", light_type))
+            })
+        };
+        let direction = || {
+            let direction = light.direction.map(Vec3::from_array).ok_or_else(|| {
+                RaystError::InvalidInput(format!("{} light requires a direction", light_type))
+            })?;
+            if direction.length_squared() < 1e-12 {
+                return Err(RaystError::InvalidInput(format!(
+                    "{} light direction must not be zero",
+                    light_type
+                )));
+            }
+            Ok(direction)
+        };
+        let profile = || -> Result<Option<IesProfile>> {
+            match &light.ies_profile {
+                Some(file) if light_type == "directional" => {
+                    Err(RaystError::InvalidInput(format!(
+                        "IES profile '{}' cannot be used on a directional light",
+                        file
+                    )))
+                }
+                Some(file) => {
+                    let mut profile = IesProfile::load_from_file(self.resolve_path(file))?;
+                    profile.rotation = light.ies_rotation.unwrap_or(0.0);
+                    Ok(Some(profile))
+                }
+                None => Ok(None),
+            }
+        };
+
+        let light: Box<dyn Light> = match light_type {
+            "point" => {
+                let mut point = PointLight::new(position()?, color, light.intensity);
+                point.profile = profile()?;
+                Box::new(point)
+            }
+            "spot" => {
+                let outer_angle = light.outer_angle.unwrap_or(30.0);
+                let inner_angle = light.inner_angle.unwrap_or(outer_angle * 0.8);
+                let valid = outer_angle > 0.0
+                    && outer_angle <= 180.0
+                    && (0.0..=outer_angle).contains(&inner_angle);
+                if !valid {
+                    return Err(RaystError::InvalidInput(format!(
+                        "Spot light needs 0 <= inner_angle <= outer_angle <= 180 degrees, got {} and {}",
+                        inner_angle, outer_angle
+                    )));
+                }
+
+                let mut spot = SpotLight::new(
+                    position()?,
+                    direction()?,
+                    color,
+                    light.intensity,
+                    inner_angle,
+                    outer_angle,
+                );
+                spot.profile = profile()?;
+                Box::new(spot)
+            }
+            "directional" => {
+                profile()?;
+                let direction = direction()?;
+
+                // Default to the apparent size of the sun
+                let angular_diameter = light.angular_diameter.unwrap_or(0.53);
+                if !(0.0..180.0).contains(&angular_diameter) {
+                    return Err(RaystError::InvalidInput(format!(
+                        "Directional light angular_diameter must be between 0 and 180 degrees, got {}",
+                        angular_diameter
+                    )));
+                }
+
+                Box::new(DirectionalLight::new(
+                    direction,
+                    color,
+                    light.intensity,
+                    angular_diameter,
+                ))
+            }
+            other => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Unknown light type '{}', expected one of: point, spot, directional, rect, disk, sphere",
+                    other
+                )));
+            }
+        };
+
+        Ok(light)
+    }
+
+    // Area lights are emissive shapes, so they are visible to camera and
+    // reflection rays and sampled like any other emitter
+    pub fn to_area_light(
+        &self,
+        light: &LightConfig,
+    ) -> Result<Box<dyn crate::geometry::Primitive>> {
+        use crate::geometry::*;
+
+        let light_type = light.light_type.as_deref().unwrap_or("point");
+        let position = Vec3::from_array(light.position.ok_or_else(|| {
+            RaystError::InvalidInput(format!("{} light requires a position", light_type))
+        })?);
+        let facing = Vec3::from_array(light.direction.unwrap_or([0.0, -1.0, 0.0]));
+        if facing.length_squared() < 1e-12 {
+            return Err(RaystError::InvalidInput(format!(
+                "{} light direction must not be zero",
+                light_type
+            )));
+        }
+
+        let dimension = |value: Option<f32>, name: &str| match value {
+            Some(value) if value > 0.0 => Ok(value),
+            Some(value) => Err(RaystError::InvalidInput(format!(
+                "{} light {} must be positive, got {}",
+                light_type, name, value
+            ))),
+            None => Err(RaystError::InvalidInput(format!(
+                "{} light requires a {}",
+                light_type, name
+            ))),
+        };
+
+        let mut material = crate::materials::Material::new(Vec3::ZERO, 0.0, 1.0);
+        material.emission = light.linear_color() * light.intensity;
+
+        let light: Box<dyn Primitive> = match light_type {
+            "rect" => Box::new(Rectangle::new(
+                position,
+                facing,
+                dimension(light.width, "width")?,
+                dimension(light.height, "height")?,
+                material,
+            )),
+            "disk" => Box::new(Disk::facing(
+                position,
+                facing,
+                dimension(light.radius, "radius")?,
+                material,
+            )),
+            "sphere" => Box::new(Sphere::new(
+                position,
+                dimension(light.radius, "radius")?,
+                material,
+            )),
+            other => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Light type '{}' is not an area light",
+                    other
+                )));
+            }
+        };
+
+        Ok(light)
+    }
+
+    pub fn to_environment(&self) -> Result<Option<crate::lighting::EnvironmentMap>> {
+        let intensity = self.background_intensity()?;
+
+        if let Some(sky) = self.to_sky()? {
+            let [width, height] = self
+                .background
+                .sky
+                .as_ref()
+                .and_then(|sky| sky.resolution)
+                .unwrap_or([512, 256]);
+            return sky.to_environment(width, height, intensity).map(Some);
+        }
+
+        let Some(file) = &self.background.environment else {
+            return Ok(None);
+        };
+
+        let environment = crate::lighting::EnvironmentMap::load_from_file(
+            self.resolve_path(file),
+            self.background.rotation.unwrap_or(0.0),
+            intensity,
+        )?;
+
+        Ok(Some(environment))
+    }
+
+    // The procedural sky comes with a sun light matching its sun position
+    pub fn to_sky_sun(&self) -> Result<Option<Box<dyn crate::lighting::Light>>> {
+        let intensity = self.background_intensity()?;
+        Ok(self
+            .to_sky()?
+            .map(|sky| Box::new(sky.sun_light(intensity)) as Box<dyn crate::lighting::Light>))
+    }
+
+    fn to_sky(&self) -> Result<Option<crate::lighting::SkyModel>> {
+        let Some(sky) = &self.background.sky else {
+            return Ok(None);
+        };
+
+        if self.background.environment.is_some() {
+            return Err(RaystError::InvalidInput(String::from(
+                "Background accepts either an environment map or a sky, not both",
+            )));
+        }
+
+        crate::lighting::SkyModel::new(
+            sky.sun_elevation,
+            sky.sun_azimuth.unwrap_or(0.0),
+            sky.turbidity.unwrap_or(3.0),
         )
+        .map(Some)
+    }
+
+    fn background_intensity(&self) -> Result<f32> {
+        let intensity = self.background.intensity.unwrap_or(1.0);
+        if intensity < 0.0 {
+            return Err(RaystError::InvalidInput(format!(
+                "Background intensity must not be negative, got {}",
+                intensity
+            )));
+        }
+        Ok(intensity)
+    }
+
+    fn to_projection(&self) -> Result<Box<dyn crate::scene::Projection>> {
+        use crate::scene::*;
+
+        let fov = self.camera.fov;
+        let projection = self.camera.projection.as_deref().unwrap_or("perspective");
+
+        let fov_range = match projection {
+            "perspective" => Some((fov > 0.0 && fov < 180.0, "below 180")),
+            "fisheye_equidistant" | "fisheye_equisolid" => {
+                Some((fov > 0.0 && fov <= 360.0, "at most 360"))
+            }
+            _ => None,
+        };
+        if let Some((false, limit)) = fov_range {
+            return Err(RaystError::InvalidInput(format!(
+                "Camera fov for the {} projection must be positive and {} degrees, got {}",
+                projection, limit, fov
+            )));
+        }
+
+        let projection: Box<dyn Projection> = match projection {
+            "perspective" => Box::new(Perspective::new(fov)),
+            "orthographic" => {
+                let width = self.camera.ortho_width.ok_or_else(|| {
+                    RaystError::InvalidInput(String::from(
+                        "Orthographic camera requires an ortho_width",
+                    ))
+                })?;
+                if width <= 0.0 {
+                    return Err(RaystError::InvalidInput(format!(
+                        "Camera ortho_width must be positive, got {}",
+                        width
+                    )));
+                }
+                Box::new(Orthographic::new(width))
+            }
+            "equirectangular" => Box::new(Equirectangular),
+            "fisheye_equidistant" => Box::new(Fisheye::new(fov, FisheyeMapping::Equidistant)),
+            "fisheye_equisolid" => Box::new(Fisheye::new(fov, FisheyeMapping::Equisolid)),
+            other => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Unknown camera projection '{}', expected one of: perspective, orthographic, equirectangular, fisheye_equidistant, fisheye_equisolid",
+                    other
+                )));
+            }
+        };
+
+        Ok(projection)
+    }
+
+    pub fn to_integrator(&self) -> Result<Box<dyn crate::integrators::Integrator>> {
+        use crate::integrators::*;
+
+        let max_depth = self.render.max_depth;
+        let integrator: Box<dyn Integrator> = match self.render.integrator.as_deref() {
+            None | Some("whitted") => Box::new(WhittedIntegrator::new(max_depth)),
+            Some("path") => Box::new(PathIntegrator::new(max_depth)),
+            Some("normal") => Box::new(NormalIntegrator),
+            Some("depth") => Box::new(DepthIntegrator::new(
+                self.render.depth_range.unwrap_or(20.0),
+            )),
+            Some("albedo") => Box::new(AlbedoIntegrator),
+            Some("uv") => Box::new(UvIntegrator),
+            Some(other) => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Unknown integrator '{}', expected one of: whitted, path, normal, depth, albedo, uv",
+                    other
+                )));
+            }
+        };
+
+        Ok(integrator)
+    }
+
+    pub fn to_tone_mapper(&self) -> Result<crate::renderer::ToneMapper> {
+        use crate::renderer::ToneMapping;
+
+        let operator = match self.render.tone_mapping.as_deref() {
+            None | Some("reinhard") => ToneMapping::Reinhard,
+            Some("none") | Some("clamp") => ToneMapping::Clamp,
+            Some("reinhard_extended") => ToneMapping::ReinhardExtended {
+                white_point: self.render.white_point.unwrap_or(4.0),
+            },
+            Some("aces") => ToneMapping::Aces,
+            Some("agx") => ToneMapping::AgX,
+            Some(other) => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Unknown tone mapping '{}', expected one of: none, clamp, reinhard, reinhard_extended, aces, agx",
+                    other
+                )));
+            }
+        };
+
+        Ok(crate::renderer::ToneMapper::new(
+            operator,
+            self.render.exposure.unwrap_or(0.0),
+        ))
+    }
+
+    // Color textures decode 8-bit images from sRGB, data textures such as
+    // roughness or normal maps read them as linear unless told otherwise
+    pub fn to_texture(
+        &self,
+        config: &TextureConfig,
+        color: bool,
+    ) -> Result<std::sync::Arc<dyn crate::textures::Texture>> {
+        use crate::textures::*;
+        use std::sync::Arc;
+
+        let wrap_mode = |wrap: &Option<String>, default: WrapMode| match wrap.as_deref() {
+            None => Ok(default),
+            Some(name) => WrapMode::from_name(name).ok_or_else(|| {
+                RaystError::InvalidInput(format!(
+                    "Unknown texture wrap mode '{}', expected one of: repeat, mirror, clamp",
+                    name
+                ))
+            }),
+        };
+
+        let texture: Arc<dyn Texture> = match config {
+            TextureConfig::Image {
+                file,
+                wrap,
+                scale,
+                linear,
+            } => {
+                let mut texture = ImageTexture::load_from_file(
+                    self.resolve_path(file),
+                    linear.unwrap_or(!color),
+                )?;
+                texture.wrap = wrap_mode(wrap, WrapMode::Repeat)?;
+                texture.scale = glam::Vec2::from_array(scale.unwrap_or([1.0, 1.0]));
+                Arc::new(texture)
+            }
+            TextureConfig::Checker { even, odd, scale } => Arc::new(CheckerTexture::new(
+                Vec3::from_array(*even),
+                Vec3::from_array(*odd),
+                scale.unwrap_or(8.0),
+            )),
+            TextureConfig::Gradient {
+                start,
+                end,
+                direction,
+                wrap,
+            } => {
+                let mut texture = GradientTexture::new(
+                    Vec3::from_array(*start),
+                    Vec3::from_array(*end),
+                    glam::Vec2::from_array(direction.unwrap_or([0.0, 1.0])),
+                );
+                texture.wrap = wrap_mode(wrap, WrapMode::Clamp)?;
+                Arc::new(texture)
+            }
+            TextureConfig::Noise {
+                low,
+                high,
+                scale,
+                octaves,
+                seed,
+            } => Arc::new(NoiseTexture::new(
+                Vec3::from_array(*low),
+                Vec3::from_array(*high),
+                scale.unwrap_or(1.0),
+                octaves.unwrap_or(4),
+                seed.unwrap_or(0),
+            )),
+        };
+
+        Ok(texture)
+    }
+
+    pub fn to_transform(&self, config: &TransformConfig) -> Result<crate::math::Transform> {
+        use crate::math::{Transform, euler_rotation};
+
+        let position = Vec3::from_array(config.position.unwrap_or([0.0, 0.0, 0.0]));
+        let scale = Vec3::from_array(config.scale.unwrap_or([1.0, 1.0, 1.0]));
+        if scale.cmpeq(Vec3::ZERO).any() {
+            return Err(RaystError::InvalidInput(format!(
+                "Transform scale must be non-zero on every axis, got {:?}",
+                config.scale.unwrap_or_default()
+            )));
+        }
+
+        match (config.rotation, config.quaternion) {
+            (Some(_), Some(_)) => Err(RaystError::InvalidInput(String::from(
+                "Transform takes either rotation or quaternion, not both",
+            ))),
+            (_, Some(quaternion)) => {
+                let quaternion = glam::Quat::from_array(quaternion);
+                if quaternion.length_squared() <= f32::EPSILON {
+                    return Err(RaystError::InvalidInput(String::from(
+                        "Transform quaternion must not be zero",
+                    )));
+                }
+                Ok(Transform::from_quaternion(position, quaternion, scale))
+            }
+            (rotation, None) => Ok(Transform::new(
+                position,
+                euler_rotation(Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]))),
+                scale,
+            )),
+        }
+    }
+
+    pub fn to_material_library(&self) -> Result<crate::materials::MaterialLibrary> {
+        let mut library = crate::materials::MaterialLibrary::new();
+        for (name, config) in &self.materials {
+            let material = self.to_material(config).map_err(|e| match e {
+                RaystError::InvalidInput(msg) => {
+                    RaystError::InvalidInput(format!("Material '{}': {}", name, msg))
+                }
+                other => other,
+            })?;
+            library.insert(name.clone(), material);
+        }
+        Ok(library)
     }
 
-    pub fn to_material(&self, config: &MaterialConfig) -> crate::materials::Material {
-        match config.material_type.as_str() {
+    pub fn resolve_material(
+        &self,
+        material: &MaterialRef,
+        library: &crate::materials::MaterialLibrary,
+    ) -> Result<crate::materials::Material> {
+        match material {
+            MaterialRef::Named(name) => library.get(name),
+            MaterialRef::Inline(config) => self.to_material(config),
+        }
+    }
+
+    pub fn to_material(&self, config: &MaterialConfig) -> Result<crate::materials::Material> {
+        let mut material = match config.material_type.as_str() {
             "plastic" => crate::materials::Material::plastic(Vec3::from_array(config.albedo)),
             "metal" => crate::materials::Material::metal(
                 Vec3::from_array(config.albedo),
@@ -116,11 +1162,740 @@ impl SceneConfig {
             "gold" => crate::materials::Material::gold(),
             "silver" => crate::materials::Material::silver(),
             "copper" => crate::materials::Material::copper(),
-            _ => crate::materials::Material::new(
+            "glass" => crate::materials::Material::glass(),
+            "water" => crate::materials::Material::water(),
+            "standard" => crate::materials::Material::new(
                 Vec3::from_array(config.albedo),
                 config.metallic.unwrap_or(0.0),
                 config.roughness.unwrap_or(0.5),
             ),
+            other => {
+                return Err(RaystError::InvalidInput(format!(
+                    "Unknown material_type '{}', expected one of: standard, plastic, metal, gold, silver, copper, glass, water",
+                    other
+                )));
+            }
+        };
+
+        if let Some(emission) = config.emission {
+            if emission
+                .iter()
+                .any(|&component| component < 0.0 || component.is_nan())
+            {
+                return Err(RaystError::InvalidInput(format!(
+                    "Material emission must not be negative, got {:?}",
+                    emission
+                )));
+            }
+            material.emission = Vec3::from_array(emission);
+        }
+        if let Some(transparency) = config.transparency {
+            if !(0.0..=1.0).contains(&transparency) {
+                return Err(RaystError::InvalidInput(format!(
+                    "Material transparency must be between 0 and 1, got {}",
+                    transparency
+                )));
+            }
+            material.transparency = transparency;
+        }
+        if let Some(ior) = config.ior {
+            if ior < 1.0 || ior.is_nan() {
+                return Err(RaystError::InvalidInput(format!(
+                    "Material ior must be at least 1, got {}",
+                    ior
+                )));
+            }
+            material.ior = ior;
+        }
+        if let Some(absorption) = config.absorption {
+            if absorption < 0.0 || absorption.is_nan() {
+                return Err(RaystError::InvalidInput(format!(
+                    "Material absorption must not be negative, got {}",
+                    absorption
+                )));
+            }
+            material.absorption = absorption;
+        }
+
+        let texture = |config: &Option<TextureConfig>, color: bool| {
+            config
+                .as_ref()
+                .map(|texture| self.to_texture(texture, color))
+                .transpose()
+        };
+        material.albedo_texture = texture(&config.albedo_texture, true)?;
+        material.metallic_texture = texture(&config.metallic_texture, false)?;
+        material.roughness_texture = texture(&config.roughness_texture, false)?;
+        material.ao_texture = texture(&config.ao_texture, false)?;
+        material.normal_texture = texture(&config.normal_map, false)?;
+        material.bump_texture = texture(&config.bump_map, false)?;
+        if let Some(strength) = config.bump_strength {
+            material.bump_strength = strength;
+        }
+
+        Ok(material)
+    }
+}
+
+// Builds scene primitives from object configs. Named groups are built once
+// on first use and shared by all of their instances.
+pub struct ObjectBuilder<'a> {
+    config: &'a SceneConfig,
+    library: crate::materials::MaterialLibrary,
+    groups: HashMap<String, Arc<crate::geometry::Group>>,
+    building: Vec<String>,
+}
+
+impl<'a> ObjectBuilder<'a> {
+    pub fn new(config: &'a SceneConfig) -> Result<Self> {
+        Ok(Self {
+            config,
+            library: config.to_material_library()?,
+            groups: HashMap::new(),
+            building: Vec::new(),
+        })
+    }
+
+    pub fn build(
+        &mut self,
+        obj_config: &ObjectConfig,
+    ) -> Result<Vec<Box<dyn crate::geometry::Primitive>>> {
+        use crate::geometry::*;
+
+        let object: Box<dyn Primitive> = match obj_config {
+            ObjectConfig::Sphere {
+                position,
+                radius,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                Box::new(Sphere::new(
+                    Vec3::from_array(*position),
+                    check_size("sphere", "radius", *radius, false)?,
+                    mat,
+                ))
+            }
+            ObjectConfig::Plane {
+                position,
+                normal,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                Box::new(Plane::new(
+                    Vec3::from_array(*position),
+                    Vec3::from_array(*normal),
+                    mat,
+                ))
+            }
+            ObjectConfig::Cube {
+                position,
+                size,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Cube::new(
+                    Vec3::from_array(*position),
+                    rot,
+                    check_size("cube", "size", *size, false)?,
+                    mat,
+                ))
+            }
+            ObjectConfig::Pyramid {
+                position,
+                base_size,
+                height,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Pyramid::new(
+                    Vec3::from_array(*position),
+                    check_size("pyramid", "base_size", *base_size, false)?,
+                    check_size("pyramid", "height", *height, false)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Cylinder {
+                position,
+                radius,
+                height,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Cylinder::new(
+                    Vec3::from_array(*position),
+                    check_size("cylinder", "radius", *radius, false)?,
+                    check_size("cylinder", "height", *height, false)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Cone {
+                position,
+                radius,
+                height,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Cone::new(
+                    Vec3::from_array(*position),
+                    check_size("cone", "radius", *radius, false)?,
+                    check_size("cone", "height", *height, false)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Disk {
+                position,
+                radius,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Disk::new(
+                    Vec3::from_array(*position),
+                    check_size("disk", "radius", *radius, false)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Torus {
+                position,
+                major_radius,
+                minor_radius,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Torus::new(
+                    Vec3::from_array(*position),
+                    check_size("torus", "major_radius", *major_radius, false)?,
+                    check_size("torus", "minor_radius", *minor_radius, false)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Capsule {
+                position,
+                radius,
+                height,
+                rotation,
+                material,
+            } => {
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Capsule::new(
+                    Vec3::from_array(*position),
+                    check_size("capsule", "radius", *radius, false)?,
+                    // A capsule without a straight section is a sphere
+                    check_size("capsule", "height", *height, true)?,
+                    rot,
+                    mat,
+                ))
+            }
+            ObjectConfig::Group { objects, transform } => {
+                let transform = self.config.to_transform(transform)?;
+                let mut placed: Vec<Box<dyn Primitive>> = Vec::new();
+                for object in objects {
+                    for primitive in self.build(object)? {
+                        placed.push(Box::new(Transformed::new(primitive, transform)));
+                    }
+                }
+                return Ok(placed);
+            }
+            ObjectConfig::Csg { operation, objects } => {
+                let csg_operation = match operation.as_str() {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    "difference" => CsgOperation::Difference,
+                    other => {
+                        return Err(RaystError::InvalidInput(format!(
+                            "Unknown CSG operation '{}', expected one of: union, intersection, difference",
+                            other
+                        )));
+                    }
+                };
+                if objects.len() < 2 {
+                    return Err(RaystError::InvalidInput(format!(
+                        "CSG {} needs at least two objects, got {}",
+                        operation,
+                        objects.len()
+                    )));
+                }
+
+                // Operands that build to several primitives, like groups,
+                // act as their union
+                let mut operands = Vec::new();
+                for object in objects {
+                    let parts = self.build(object)?;
+                    if parts.iter().any(|part| !part.is_solid()) {
+                        return Err(RaystError::InvalidInput(format!(
+                            "CSG {} objects must be closed shapes; planes, disks and meshes can't be used",
+                            operation
+                        )));
+                    }
+                    let operand = parts
+                        .into_iter()
+                        .reduce(|left, right| Box::new(Csg::new(CsgOperation::Union, left, right)));
+                    operands.extend(operand);
+                }
+
+                operands
+                    .into_iter()
+                    .reduce(|left, right| Box::new(Csg::new(csg_operation, left, right)))
+                    .ok_or_else(|| {
+                        RaystError::InvalidInput(format!("CSG {} has no objects", operation))
+                    })?
+            }
+            ObjectConfig::Instance { group, transform } => {
+                let transform = self.config.to_transform(transform)?;
+                Box::new(Transformed::new(self.group(group)?, transform))
+            }
+            ObjectConfig::Mesh {
+                file,
+                position,
+                scale,
+                rotation,
+                material,
+                materials,
+                crease_angle,
+            } => {
+                if let Some(angle) = crease_angle
+                    && !(0.0..=180.0).contains(angle)
+                {
+                    return Err(RaystError::InvalidInput(format!(
+                        "Mesh crease_angle must be between 0 and 180 degrees, got {}",
+                        angle
+                    )));
+                }
+
+                let mut scale = scale.unwrap_or([1.0, 1.0, 1.0]);
+                for component in &mut scale {
+                    *component = check_size("mesh", "scale", *component, false)?;
+                }
+                let scale = Vec3::from_array(scale);
+
+                let mat = self.config.resolve_material(material, &self.library)?;
+                let group_materials: HashMap<String, crate::materials::Material> = materials
+                    .iter()
+                    .flatten()
+                    .map(|(name, material)| {
+                        Ok((
+                            name.clone(),
+                            self.config.resolve_material(material, &self.library)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?;
+                let model =
+                    crate::loaders::ObjModel::load_from_file(self.config.resolve_path(file))?;
+
+                // Once a materials table is given, every usemtl name needs an entry
+                if materials.is_some()
+                    && let Some(name) =
+                        model.unmapped_material(|name| group_materials.contains_key(name))
+                {
+                    let mut names: Vec<&str> = group_materials.keys().map(String::as_str).collect();
+                    names.sort();
+                    return Err(RaystError::InvalidInput(format!(
+                        "Mesh '{}' uses material '{}', which is not in its materials table; expected one of: {}",
+                        file,
+                        name,
+                        names.join(", ")
+                    )));
+                }
+
+                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
+                Box::new(Mesh::from_obj(
+                    &model,
+                    Vec3::from_array(*position),
+                    scale,
+                    rot,
+                    &mat,
+                    &group_materials,
+                    *crease_angle,
+                ))
+            }
+        };
+        Ok(vec![object])
+    }
+
+    fn group(&mut self, name: &str) -> Result<Arc<crate::geometry::Group>> {
+        if let Some(group) = self.groups.get(name) {
+            return Ok(group.clone());
+        }
+
+        if self.building.iter().any(|building| building == name) {
+            let mut chain = self.building.clone();
+            chain.push(name.to_string());
+            return Err(RaystError::InvalidInput(format!(
+                "Group instance cycle: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        let Some(group_config) = self.config.groups.get(name) else {
+            let mut names: Vec<&str> = self.config.groups.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            return Err(RaystError::InvalidInput(if names.is_empty() {
+                format!("Unknown group '{}', the scene defines no groups", name)
+            } else {
+                format!(
+                    "Unknown group '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            }));
+        };
+
+        self.building.push(name.to_string());
+        let mut objects = Vec::new();
+        for object in &group_config.objects {
+            objects.extend(self.build(object)?);
+        }
+        self.building.pop();
+
+        let group = Arc::new(crate::geometry::Group::new(name, objects));
+        self.groups.insert(name.to_string(), group.clone());
+        Ok(group)
+    }
+}
+
+// Every object dimension goes through here so that all sizes share one
+// rule: finite and above zero, or at least zero where a size may vanish
+fn check_size(object_type: &str, name: &str, value: f32, allow_zero: bool) -> Result<f32> {
+    if value.is_finite() && (value > 0.0 || allow_zero && value == 0.0) {
+        Ok(value)
+    } else {
+        Err(RaystError::InvalidInput(format!(
+            "{} {} must be {}, got {}",
+            object_type,
+            name,
+            if allow_zero {
+                "zero or positive"
+            } else {
+                "positive"
+            },
+            value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(extra: &str) -> SceneConfig {
+        toml::from_str(&format!(
+            r#"
+            objects = []
+
+            [camera]
+            position = [0.0, 0.0, 0.0]
+            direction = [0.0, 0.0, -1.0]
+            fov = 60.0
+            {}
+
+            [render]
+            width = 1
+            height = 1
+            max_depth = 1
+            antialiasing = 1
+            output_file = "out.png"
+
+            [background]
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn error_message<T>(result: Result<T>) -> String {
+        match result {
+            Ok(_) => panic!("expected an error"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn light_colors_share_one_unit() {
+        let config = scene("");
+        let light = |light_type: &str| LightConfig {
+            light_type: Some(light_type.to_string()),
+            position: Some([0.0, 1.0, 0.0]),
+            direction: Some([0.0, -1.0, 0.0]),
+            angular_diameter: Some(0.0),
+            inner_angle: None,
+            outer_angle: None,
+            ies_profile: None,
+            ies_rotation: None,
+            width: None,
+            height: None,
+            radius: Some(0.5),
+            color: [255, 51, 0],
+            intensity: 2.0,
+        };
+        let expected = Vec3::new(2.0, 0.4, 0.0);
+
+        for light_type in ["point", "directional"] {
+            let radiance = config
+                .to_light(&light(light_type))
+                .unwrap()
+                .illuminate(Vec3::ZERO)
+                .radiance;
+            assert!(radiance.abs_diff_eq(expected, 1e-6), "{}", light_type);
+        }
+
+        let disk = config.to_area_light(&light("disk")).unwrap();
+        let hit = disk
+            .hit(&crate::math::Ray::new(Vec3::ZERO, Vec3::Y))
+            .unwrap();
+        assert!(hit.material.emission.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn out_of_range_material_values_are_rejected() {
+        let config = scene("");
+        let material = |extra: &str| {
+            let material: MaterialConfig = toml::from_str(&format!(
+                "material_type = \"standard\"\nalbedo = [0.5, 0.5, 0.5]\n{}",
+                extra
+            ))
+            .unwrap();
+            config.to_material(&material)
+        };
+
+        for (extra, expected) in [
+            (
+                "emission = [1.0, -0.5, 0.0]",
+                "emission must not be negative",
+            ),
+            ("transparency = 1.5", "transparency must be between 0 and 1"),
+            ("ior = 0.5", "ior"),
+            ("absorption = -1.0", "absorption"),
+        ] {
+            let message = error_message(material(extra));
+            assert!(message.contains(expected), "{}", message);
+        }
+
+        let glowing = material("emission = [4.0, 2.0, 0.0]").unwrap();
+        assert_eq!(glowing.emission, Vec3::new(4.0, 2.0, 0.0));
+    }
+
+    // Writes `files` into a fresh directory under the system temp directory
+    fn write_scene_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("rayst-{}-{}", name, std::process::id()));
+        for (file, content) in files {
+            let path = directory.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        directory
+    }
+
+    const CAMERA: &str = "[camera]\nposition = [0.0, 0.0, 0.0]\ndirection = [0.0, 0.0, -1.0]\n";
+    const RENDER: &str = "[render]\nheight = 1\nmax_depth = 1\nantialiasing = 1\n";
+
+    #[test]
+    fn include_cycles_are_rejected() {
+        let directory = write_scene_files(
+            "include-cycle",
+            &[
+                ("scene.toml", "include = [\"parts/a.toml\"]"),
+                ("parts/a.toml", "include = [\"b.toml\"]"),
+                ("parts/b.toml", "include = [\"../scene.toml\"]"),
+                ("itself.toml", "include = [\"itself.toml\"]"),
+            ],
+        );
+
+        for scene in ["scene.toml", "itself.toml"] {
+            let path = directory.join(scene);
+            let message = error_message(SceneConfig::load_from_file(path.to_str().unwrap()));
+            assert!(message.contains("Scene include cycle"), "{}", message);
+        }
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn later_files_win_and_objects_accumulate() {
+        let sphere = |x: f32| {
+            format!(
+                "[[objects]]\nobject_type = \"sphere\"\nposition = [{:.1}, 0.0, 0.0]\nradius = 1.0\nmaterial = \"m\"\n",
+                x
+            )
+        };
+        let base = format!(
+            "{}fov = 40.0\n{}width = 10\noutput_file = \"base.png\"\n[background]\ncolor = [1, 2, 3]\n{}",
+            CAMERA,
+            RENDER,
+            sphere(1.0)
+        );
+        let part = format!(
+            "{}width = 20\noutput_file = \"part.png\"\n{}",
+            RENDER,
+            sphere(2.0)
+        );
+        let scene = format!(
+            "include = [\"base.toml\", \"part.toml\"]\n{}{}fov = 70.0\n",
+            sphere(3.0),
+            CAMERA
+        );
+        let directory = write_scene_files(
+            "include-merge",
+            &[
+                ("base.toml", &base),
+                ("part.toml", &part),
+                ("scene.toml", &scene),
+            ],
+        );
+
+        let config =
+            SceneConfig::load_from_file(directory.join("scene.toml").to_str().unwrap()).unwrap();
+        assert_eq!(config.camera.fov, 70.0);
+        assert_eq!(config.render.width, 20);
+        assert_eq!(config.background.color, Some([1, 2, 3]));
+
+        let positions: Vec<f32> = config
+            .objects
+            .iter()
+            .map(|object| match object {
+                ObjectConfig::Sphere { position, .. } => position[0],
+                other => panic!("unexpected object {:?}", other),
+            })
+            .collect();
+        assert_eq!(positions, [1.0, 2.0, 3.0]);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn negative_aperture_is_rejected() {
+        let message = error_message(scene("aperture = -0.1\nfocus_distance = 2.0").to_camera());
+        assert!(
+            message.contains("aperture must not be negative"),
+            "{}",
+            message
+        );
+
+        assert!(scene("aperture = 0.0").to_camera().is_ok());
+    }
+
+    fn build_object(config: &SceneConfig, object: &str) -> Result<usize> {
+        let object: ObjectConfig = toml::from_str(&format!(
+            "{}\nmaterial = {{ material_type = \"standard\", albedo = [0.5, 0.5, 0.5] }}",
+            object
+        ))
+        .unwrap();
+        Ok(ObjectBuilder::new(config)?.build(&object)?.len())
+    }
+
+    #[test]
+    fn object_sizes_are_validated() {
+        let config = scene("");
+        for (object, expected) in [
+            (
+                "object_type = \"sphere\"\nposition = [0.0, 0.0, 0.0]\nradius = 0.0",
+                "sphere radius must be positive, got 0",
+            ),
+            (
+                "object_type = \"cube\"\nposition = [0.0, 0.0, 0.0]\nsize = -1.0",
+                "cube size must be positive, got -1",
+            ),
+            (
+                "object_type = \"pyramid\"\nposition = [0.0, 0.0, 0.0]\nbase_size = 1.0\nheight = nan",
+                "pyramid height must be positive",
+            ),
+            (
+                "object_type = \"cylinder\"\nposition = [0.0, 0.0, 0.0]\nradius = inf\nheight = 1.0",
+                "cylinder radius must be positive",
+            ),
+            (
+                "object_type = \"cone\"\nposition = [0.0, 0.0, 0.0]\nradius = 1.0\nheight = 0.0",
+                "cone height must be positive",
+            ),
+            (
+                "object_type = \"disk\"\nposition = [0.0, 0.0, 0.0]\nradius = -0.5",
+                "disk radius must be positive",
+            ),
+            (
+                "object_type = \"torus\"\nposition = [0.0, 0.0, 0.0]\nmajor_radius = 1.0\nminor_radius = 0.0",
+                "torus minor_radius must be positive",
+            ),
+            (
+                "object_type = \"capsule\"\nposition = [0.0, 0.0, 0.0]\nradius = 0.5\nheight = -1.0",
+                "capsule height must be zero or positive, got -1",
+            ),
+            (
+                "object_type = \"mesh\"\nfile = \"missing.obj\"\nposition = [0.0, 0.0, 0.0]\nscale = [1.0, 0.0, 1.0]",
+                "mesh scale must be positive, got 0",
+            ),
+        ] {
+            let message = error_message(build_object(&config, object));
+            assert!(message.contains(expected), "{}", message);
+        }
+
+        // A capsule without a straight section is a sphere
+        let sphere =
+            "object_type = \"capsule\"\nposition = [0.0, 0.0, 0.0]\nradius = 0.5\nheight = 0.0";
+        assert_eq!(build_object(&config, sphere).unwrap(), 1);
+    }
+
+    #[test]
+    fn csg_and_group_errors_are_reported() {
+        let sphere = "{ object_type = \"sphere\", position = [0.0, 0.0, 0.0], radius = 1.0, material = \"white\" }";
+        let mut config = scene("");
+        config.materials =
+            toml::from_str("[white]\nmaterial_type = \"standard\"\nalbedo = [0.5, 0.5, 0.5]")
+                .unwrap();
+        config.groups = toml::from_str(
+            "[a]\nobjects = [{ object_type = \"instance\", group = \"b\" }]\n\
+             [b]\nobjects = [{ object_type = \"instance\", group = \"a\" }]",
+        )
+        .unwrap();
+
+        for (object, expected) in [
+            (
+                format!(
+                    "object_type = \"csg\"\noperation = \"xor\"\nobjects = [{0}, {0}]",
+                    sphere
+                ),
+                "Unknown CSG operation 'xor'",
+            ),
+            (
+                format!(
+                    "object_type = \"csg\"\noperation = \"union\"\nobjects = [{}]",
+                    sphere
+                ),
+                "CSG union needs at least two objects, got 1",
+            ),
+            (
+                format!(
+                    "object_type = \"csg\"\noperation = \"intersection\"\nobjects = [{}, {}]",
+                    sphere,
+                    "{ object_type = \"plane\", position = [0.0, 0.0, 0.0], normal = [0.0, 1.0, 0.0], material = \"white\" }"
+                ),
+                "CSG intersection objects must be closed shapes",
+            ),
+            (
+                String::from("object_type = \"instance\"\ngroup = \"a\""),
+                "Group instance cycle: a -> b -> a",
+            ),
+            (
+                String::from("object_type = \"instance\"\ngroup = \"c\""),
+                "Unknown group 'c', expected one of: a, b",
+            ),
+        ] {
+            let object: ObjectConfig = toml::from_str(&object).unwrap();
+            let message = error_message(ObjectBuilder::new(&config).unwrap().build(&object));
+            assert!(message.contains(expected), "{}", message);
         }
     }
 }
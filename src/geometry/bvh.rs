@@ -0,0 +1,347 @@
+use crate::geometry::Primitive;
+use crate::math::{Aabb, Intersection, Ray};
+use glam::Vec3;
+
+const MAX_LEAF_SIZE: usize = 4;
+const BIN_COUNT: usize = 12;
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    bounds: Aabb,
+    offset: usize,
+    count: usize,
+}
+
+impl BvhNode {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BuildItem {
+    index: usize,
+    bounds: Aabb,
+    centroid: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bin {
+    bounds: Aabb,
+    count: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new<P: Primitive>(primitives: &[P]) -> Self {
+        let mut items: Vec<BuildItem> = primitives
+            .iter()
+            .enumerate()
+            .filter_map(|(index, primitive)| {
+                primitive.bounding_box().map(|bounds| BuildItem {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                })
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(items.len() * 2);
+        if !items.is_empty() {
+            let count = items.len();
+            Self::build_node(&mut items, 0, count, &mut nodes);
+        }
+
+        Self {
+            nodes,
+            indices: items.iter().map(|item| item.index).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    fn build_node(
+        items: &mut [BuildItem],
+        start: usize,
+        end: usize,
+        nodes: &mut Vec<BvhNode>,
+    ) -> usize {
+        let node_index = nodes.len();
+        let slice = &items[start..end];
+
+        let bounds = slice
+            .iter()
+            .fold(Aabb::EMPTY, |aabb, item| aabb.union(&item.bounds));
+        let count = end - start;
+
+        nodes.push(BvhNode {
+            bounds,
+            offset: start,
+            count,
+        });
+
+        if count <= MAX_LEAF_SIZE {
+            return node_index;
+        }
+
+        let centroid_bounds = slice
+            .iter()
+            .fold(Aabb::EMPTY, |aabb, item| aabb.grow(item.centroid));
+
+        let mid = match Self::find_sah_split(slice, &bounds, &centroid_bounds) {
+            Some((axis, split_position)) => {
+                let mid = start + Self::partition(&mut items[start..end], axis, split_position);
+                if mid == start || mid == end {
+                    start + count / 2
+                } else {
+                    mid
+                }
+            }
+            None if centroid_bounds.extent().max_element() <= 0.0 => start + count / 2,
+            None => return node_index,
+        };
+
+        Self::build_node(items, start, mid, nodes);
+        let right = Self::build_node(items, mid, end, nodes);
+
+        nodes[node_index].offset = right;
+        nodes[node_index].count = 0;
+
+        node_index
+    }
+
+    fn find_sah_split(
+        items: &[BuildItem],
+        bounds: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut best_cost = INTERSECTION_COST * items.len() as f32;
+        let parent_area = bounds.surface_area().max(f32::EPSILON);
+
+        for axis in 0..3 {
+            let axis_min = centroid_bounds.min[axis];
+            let axis_extent = centroid_bounds.max[axis] - axis_min;
+
+            if axis_extent <= 0.0 {
+                continue;
+            }
+
+            let mut bins = [Bin {
+                bounds: Aabb::EMPTY,
+                count: 0,
+            }; BIN_COUNT];
+
+            let scale = BIN_COUNT as f32 / axis_extent;
+            for item in items {
+                let bin = (((item.centroid[axis] - axis_min) * scale) as usize).min(BIN_COUNT - 1);
+                bins[bin].count += 1;
+                bins[bin].bounds = bins[bin].bounds.union(&item.bounds);
+            }
+
+            let mut left_area = [0.0; BIN_COUNT - 1];
+            let mut left_count = [0usize; BIN_COUNT - 1];
+            let mut accumulated = Aabb::EMPTY;
+            let mut accumulated_count = 0;
+            for i in 0..BIN_COUNT - 1 {
+                accumulated = accumulated.union(&bins[i].bounds);
+                accumulated_count += bins[i].count;
+                left_area[i] = accumulated.surface_area();
+                left_count[i] = accumulated_count;
+            }
+
+            let mut accumulated = Aabb::EMPTY;
+            let mut accumulated_count = 0;
+            for i in (1..BIN_COUNT).rev() {
+                accumulated = accumulated.union(&bins[i].bounds);
+                accumulated_count += bins[i].count;
+
+                let split = i - 1;
+                if left_count[split] == 0 || accumulated_count == 0 {
+                    continue;
+                }
+
+                let cost = TRAVERSAL_COST
+                    + INTERSECTION_COST
+                        * (left_area[split] * left_count[split] as f32
+                            + accumulated.surface_area() * accumulated_count as f32)
+                        / parent_area;
+
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = Some((axis, axis_min + i as f32 / scale));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn partition(items: &mut [BuildItem], axis: usize, split_position: f32) -> usize {
+        let mut mid = 0;
+        for i in 0..items.len() {
+            if items[i].centroid[axis] < split_position {
+                items.swap(i, mid);
+                mid += 1;
+            }
+        }
+        mid
+    }
+
+    pub fn hit<P: Primitive>(&self, primitives: &[P], ray: &Ray) -> Option<(usize, Intersection)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let inv_dir = ray.direction.recip();
+        let mut closest_hit: Option<(usize, Intersection)> = None;
+        let mut closest_distance = f32::INFINITY;
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            if node.bounds.hit(ray, inv_dir, closest_distance).is_none() {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for &index in &self.indices[node.offset..node.offset + node.count] {
+                    if let Some(hit) = primitives[index].hit(ray)
+                        && hit.distance < closest_distance
+                        && hit.distance > 0.001
+                    {
+                        closest_distance = hit.distance;
+                        closest_hit = Some((index, hit));
+                    }
+                }
+                continue;
+            }
+
+            let left = node_index + 1;
+            let right = node.offset;
+
+            let left_distance = self.nodes[left].bounds.hit(ray, inv_dir, closest_distance);
+            let right_distance = self.nodes[right].bounds.hit(ray, inv_dir, closest_distance);
+
+            match (left_distance, right_distance) {
+                (Some(l), Some(r)) => {
+                    if l < r {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+                (Some(_), None) => stack.push(left),
+                (None, Some(_)) => stack.push(right),
+                (None, None) => {}
+            }
+        }
+
+        closest_hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Cube, Plane, Sphere, Triangle};
+    use crate::materials::Material;
+
+    fn random_point(extent: f32) -> Vec3 {
+        Vec3::new(fastrand::f32(), fastrand::f32(), fastrand::f32()) * 2.0 * extent
+            - Vec3::splat(extent)
+    }
+
+    fn random_ray() -> Ray {
+        let direction = random_point(1.0).normalize_or(Vec3::X);
+        Ray::new(random_point(15.0), direction)
+    }
+
+    // Spheres, cubes and triangles scattered through a 20 unit box, plus an
+    // unbounded plane the BVH has to leave out
+    fn random_primitives(count: usize) -> Vec<Box<dyn Primitive>> {
+        let material = Material::new(Vec3::splat(0.8), 0.0, 0.5);
+        let mut primitives: Vec<Box<dyn Primitive>> = Vec::new();
+        for i in 0..count {
+            let center = random_point(10.0);
+            let size = 0.1 + fastrand::f32();
+            primitives.push(match i % 3 {
+                0 => Box::new(Sphere::new(center, size, material.clone())),
+                1 => Box::new(Cube::new(
+                    center,
+                    random_point(90.0),
+                    size,
+                    material.clone(),
+                )),
+                _ => Box::new(Triangle::new(
+                    center,
+                    center + random_point(size),
+                    center + random_point(size),
+                    material.clone(),
+                )),
+            });
+        }
+        primitives.push(Box::new(Plane::new(-Vec3::Y * 12.0, Vec3::Y, material)));
+        primitives
+    }
+
+    fn brute_force(primitives: &[Box<dyn Primitive>], ray: &Ray) -> Option<(usize, f32)> {
+        primitives
+            .iter()
+            .enumerate()
+            .filter(|(_, primitive)| primitive.bounding_box().is_some())
+            .filter_map(|(index, primitive)| Some((index, primitive.hit(ray)?.distance)))
+            .filter(|&(_, distance)| distance > 0.001)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    #[test]
+    fn hits_match_a_brute_force_search() {
+        fastrand::seed(11);
+        let primitives = random_primitives(300);
+        let bvh = Bvh::new(&primitives);
+
+        for _ in 0..2000 {
+            let ray = random_ray();
+            let found = bvh
+                .hit(&primitives, &ray)
+                .map(|(index, hit)| (index, hit.distance));
+            assert_eq!(found, brute_force(&primitives, &ray), "ray {:?}", ray);
+        }
+    }
+
+    #[test]
+    fn empty_and_unbounded_only_scenes_never_hit() {
+        let empty: Vec<Box<dyn Primitive>> = Vec::new();
+        let bvh = Bvh::new(&empty);
+        assert!(bvh.is_empty());
+        assert!(bvh.hit(&empty, &Ray::new(Vec3::ZERO, Vec3::X)).is_none());
+
+        let planes: Vec<Box<dyn Primitive>> = vec![Box::new(Plane::new(
+            Vec3::ZERO,
+            Vec3::Y,
+            Material::new(Vec3::ONE, 0.0, 1.0),
+        ))];
+        let bvh = Bvh::new(&planes);
+        assert!(bvh.is_empty());
+        assert!(bvh.hit(&planes, &Ray::new(Vec3::Y, -Vec3::Y)).is_none());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::geometry::revolution::{self, SurfaceHit};
+use crate::geometry::{EmissionSample, Interval, Primitive};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, Transform, euler_rotation, sampling};
+use glam::Vec3;
+use std::f32::consts::PI;
+
+// Cylinder of `height` along the local Y axis closed by hemispheres, so the
+// full length is height + 2 * radius
+pub struct Capsule {
+    pub center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub rotation: Vec3,
+    pub material: Material,
+    transform: Transform,
+}
+
+impl Capsule {
+    pub fn new(center: Vec3, radius: f32, height: f32, rotation: Vec3, material: Material) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            rotation,
+            material,
+            transform: Transform::new(center, euler_rotation(rotation), Vec3::ONE),
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        2.0 * PI * self.radius * (self.height + 2.0 * self.radius)
+    }
+
+    // v follows the profile's arc length from the bottom pole to the top
+    fn surface_hit(&self, distance: f32, point: Vec3) -> SurfaceHit {
+        let half = self.height / 2.0;
+        let quarter = PI / 2.0 * self.radius;
+        let axis_point = Vec3::new(0.0, point.y.clamp(-half, half), 0.0);
+        let normal = (point - axis_point).normalize_or(Vec3::Y * point.y.signum());
+
+        let length = if point.y < -half {
+            self.radius * (-normal.y).acos()
+        } else if point.y > half {
+            quarter + self.height + self.radius * normal.y.asin()
+        } else {
+            quarter + point.y + half
+        };
+
+        SurfaceHit::side(
+            distance,
+            point,
+            normal,
+            length / (self.height + PI * self.radius),
+        )
+    }
+
+    // Distances of every place the line of the ray crosses the surface,
+    // behind its start too
+    fn crossings(&self, ray: &Ray) -> Vec<f32> {
+        let (o, d) = (ray.origin, ray.direction);
+        let half = self.height / 2.0;
+        let r2 = self.radius * self.radius;
+
+        let side = revolution::quadratic(
+            d.x * d.x + d.z * d.z,
+            2.0 * (o.x * d.x + o.z * d.z),
+            o.x * o.x + o.z * o.z - r2,
+        )
+        .into_iter()
+        .flat_map(|(t0, t1)| [t0, t1])
+        .filter(|&t| (o.y + t * d.y).abs() <= half);
+
+        // Each end only counts on its own side of the cylinder
+        let end = |y: f32| {
+            let oc = o - Vec3::Y * y;
+            revolution::quadratic(1.0, 2.0 * oc.dot(d), oc.length_squared() - r2)
+                .into_iter()
+                .flat_map(|(t0, t1)| [t0, t1])
+                .filter(move |&t| (o.y + t * d.y - y) * y.signum() >= 0.0)
+        };
+
+        side.chain(end(half)).chain(end(-half)).collect()
+    }
+
+    fn sample_surface(&self) -> (Vec3, Vec3) {
+        let half = self.height / 2.0;
+        let side = self.height / (self.height + 2.0 * self.radius);
+
+        if fastrand::f32() < side {
+            let phi = 2.0 * PI * fastrand::f32();
+            let normal = Vec3::new(phi.sin(), 0.0, -phi.cos());
+            let y = (fastrand::f32() * 2.0 - 1.0) * half;
+            (normal * self.radius + Vec3::Y * y, normal)
+        } else {
+            // The two hemispheres make a whole sphere, split at the middle
+            let normal = sampling::sample_uniform_sphere(fastrand::f32(), fastrand::f32());
+            let end = Vec3::Y * half * normal.y.signum();
+            (end + normal * self.radius, normal)
+        }
+    }
+}
+
+impl Primitive for Capsule {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, _) = self.transform.inverse_ray(ray)?;
+        let t = self
+            .crossings(&local_ray)
+            .into_iter()
+            .filter(|&t| t > 0.001)
+            .min_by(f32::total_cmp)?;
+        let hit = self.surface_hit(t, local_ray.origin + t * local_ray.direction);
+        Some(hit.to_world(&self.transform, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = self.radius.abs();
+        let extent = Vec3::new(radius, self.height.abs() / 2.0 + radius, radius);
+        Some(self.transform.aabb(&Aabb::new(-extent, extent)))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((local_ray, _)) = self.transform.inverse_ray(ray) else {
+            return Vec::new();
+        };
+
+        let crossings = self
+            .crossings(&local_ray)
+            .into_iter()
+            .map(|t| self.surface_hit(t, local_ray.origin + t * local_ray.direction));
+        revolution::convex_intervals(crossings, &self.transform, &self.material)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        revolution::emission_sample(
+            &self.transform,
+            self.sample_surface(),
+            self.area(),
+            &self.material,
+            reference,
+        )
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, hit.normal)
+    }
+}
@@ -0,0 +1,141 @@
+use crate::geometry::revolution::{self, SurfaceHit};
+use crate::geometry::{EmissionSample, Interval, Primitive};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, Transform, euler_rotation, sampling};
+use glam::Vec3;
+use std::f32::consts::PI;
+
+// Cone with its base on `base_center` and the apex `height` above it along
+// the local Y axis
+pub struct Cone {
+    pub base_center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub rotation: Vec3,
+    pub material: Material,
+    transform: Transform,
+}
+
+impl Cone {
+    pub fn new(
+        base_center: Vec3,
+        radius: f32,
+        height: f32,
+        rotation: Vec3,
+        material: Material,
+    ) -> Self {
+        Self {
+            base_center,
+            radius,
+            height,
+            rotation,
+            material,
+            transform: Transform::new(base_center, euler_rotation(rotation), Vec3::ONE),
+        }
+    }
+
+    fn slant_height(&self) -> f32 {
+        self.radius.hypot(self.height)
+    }
+
+    pub fn area(&self) -> f32 {
+        PI * self.radius * (self.radius + self.slant_height())
+    }
+
+    // Every place the line of the ray crosses the surface, behind its start
+    // too
+    fn crossings(&self, ray: &Ray) -> Vec<SurfaceHit> {
+        // x^2 + z^2 = (k (h - y))^2 with k the radius over the height
+        let (o, d) = (ray.origin, ray.direction);
+        let k2 = (self.radius / self.height).powi(2);
+        let w = self.height - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2.0 * (o.x * d.x + o.z * d.z + k2 * w * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * w * w;
+
+        // The equation also holds on the mirrored cone above the apex
+        let side = revolution::quadratic(a, b, c)
+            .into_iter()
+            .flat_map(|(t0, t1)| [t0, t1])
+            .map(|t| (t, o + t * d))
+            .filter(|(_, point)| (0.0..=self.height).contains(&point.y))
+            .map(|(t, point)| {
+                let normal =
+                    Vec3::new(point.x, k2 * (self.height - point.y), point.z).normalize_or(Vec3::Y);
+                SurfaceHit::side(t, point, normal, point.y / self.height)
+            });
+
+        side.chain(revolution::cap(ray, 0.0, self.radius, -1.0))
+            .collect()
+    }
+
+    fn sample_surface(&self) -> (Vec3, Vec3) {
+        let slant = self.slant_height();
+        if fastrand::f32() * (self.radius + slant) < self.radius {
+            return revolution::sample_cap(0.0, self.radius, -1.0);
+        }
+
+        // The side's area grows linearly away from the apex
+        let fraction = fastrand::f32().sqrt();
+        let phi = 2.0 * PI * fastrand::f32();
+        let radial = Vec3::new(phi.sin(), 0.0, -phi.cos());
+        let point = radial * self.radius * fraction + Vec3::Y * self.height * (1.0 - fraction);
+        let normal = (radial * self.height + Vec3::Y * self.radius) / slant;
+        (point, normal)
+    }
+}
+
+impl Primitive for Cone {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, _) = self.transform.inverse_ray(ray)?;
+
+        let hit = revolution::closest(self.crossings(&local_ray))?;
+        Some(hit.to_world(&self.transform, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = self.radius.abs();
+        Some(self.transform.aabb(&Aabb::new(
+            Vec3::new(-radius, self.height.min(0.0), -radius),
+            Vec3::new(radius, self.height.max(0.0), radius),
+        )))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((local_ray, _)) = self.transform.inverse_ray(ray) else {
+            return Vec::new();
+        };
+
+        revolution::convex_intervals(self.crossings(&local_ray), &self.transform, &self.material)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        revolution::emission_sample(
+            &self.transform,
+            self.sample_surface(),
+            self.area(),
+            &self.material,
+            reference,
+        )
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, hit.normal)
+    }
+}
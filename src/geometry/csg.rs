@@ -0,0 +1,130 @@
+use crate::geometry::{Interval, Primitive};
+use crate::math::{Aabb, Intersection, Ray};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+// Boolean combination of two solids, found by merging the stretches of a ray
+// inside each. Surfaces cut out of the left solid by a difference keep the
+// right solid's material, with normals turned to face out of the result.
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub left: Box<dyn Primitive>,
+    pub right: Box<dyn Primitive>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Box<dyn Primitive>,
+        right: Box<dyn Primitive>,
+    ) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+// Merges the stretches of a ray inside two solids into the stretches inside
+// their combination
+pub(crate) fn combine(
+    operation: CsgOperation,
+    left: Vec<Interval>,
+    right: Vec<Interval>,
+) -> Vec<Interval> {
+    let starts_inside =
+        |intervals: &[Interval]| intervals.first().is_some_and(|i| i.enter.is_none());
+    let mut inside = [starts_inside(&left), starts_inside(&right)];
+
+    let mut boundaries: Vec<(usize, bool, Intersection)> = Vec::new();
+    for (side, intervals) in [left, right].into_iter().enumerate() {
+        for interval in intervals {
+            boundaries.extend(interval.enter.map(|hit| (side, true, hit)));
+            boundaries.extend(interval.exit.map(|hit| (side, false, hit)));
+        }
+    }
+    boundaries.sort_by(|a, b| a.2.distance.total_cmp(&b.2.distance));
+
+    let mut intervals = Vec::new();
+    let mut was_inside = operation.contains(inside[0], inside[1]);
+    let mut enter = None;
+    for (side, entering, mut hit) in boundaries {
+        inside[side] = entering;
+        let is_inside = operation.contains(inside[0], inside[1]);
+        if is_inside == was_inside {
+            continue;
+        }
+
+        if side == 1 && operation == CsgOperation::Difference {
+            hit.normal = -hit.normal;
+            hit.shading_normal = -hit.shading_normal;
+        }
+        if is_inside {
+            enter = Some(hit);
+        } else {
+            intervals.push(Interval {
+                enter: enter.take(),
+                exit: Some(hit),
+            });
+        }
+        was_inside = is_inside;
+    }
+    if was_inside {
+        intervals.push(Interval { enter, exit: None });
+    }
+
+    intervals
+}
+
+impl Primitive for Csg {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        self.intervals(ray)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .flatten()
+            .find(|hit| hit.distance > 0.001)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let left = self.left.bounding_box();
+        let right = self.right.bounding_box();
+
+        match self.operation {
+            CsgOperation::Union => Some(left?.union(&right?)),
+            CsgOperation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(left.intersection(&right)),
+                (bounds, None) | (None, bounds) => bounds,
+            },
+            CsgOperation::Difference => left,
+        }
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let left = self.left.intervals(ray);
+        if left.is_empty() && self.operation != CsgOperation::Union {
+            return left;
+        }
+
+        combine(self.operation, left, self.right.intervals(ray))
+    }
+}
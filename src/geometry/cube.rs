@@ -1,7 +1,7 @@
-use crate::geometry::Primitive;
+use crate::geometry::{EmissionSample, Interval, Primitive, intervals_between};
 use crate::materials::Material;
-use crate::math::{Intersection, Ray};
-use glam::{Mat3, Vec3};
+use crate::math::{Aabb, Intersection, Ray, euler_rotation, sampling};
+use glam::{Mat3, Vec2, Vec3};
 
 pub struct Cube {
     pub center: Vec3,
@@ -14,13 +14,7 @@ pub struct Cube {
 
 impl Cube {
     pub fn new(center: Vec3, rotation: Vec3, size: f32, material: Material) -> Self {
-        let rot_rad = rotation * std::f32::consts::PI / 180.0;
-
-        let rot_x = Mat3::from_rotation_x(rot_rad.x);
-        let rot_y = Mat3::from_rotation_y(rot_rad.y);
-        let rot_z = Mat3::from_rotation_z(rot_rad.z);
-
-        let rotation_matrix = rot_z * rot_y * rot_x;
+        let rotation_matrix = euler_rotation(rotation);
         let inverse_rotation_matrix = rotation_matrix.transpose();
 
         Self {
@@ -50,6 +44,23 @@ impl Cube {
         (self.rotation_matrix * local_normal).normalize()
     }
 
+    // Each face maps to the full UV square, upright on the side faces.
+    // Returns the UV and the local directions of increasing u and v.
+    fn calculate_local_uv(&self, local_point: Vec3, local_normal: Vec3) -> (Vec2, Vec3, Vec3) {
+        let (tangent, bitangent) = if local_normal.x != 0.0 {
+            (Vec3::new(0.0, 0.0, -local_normal.x), Vec3::Y)
+        } else if local_normal.y != 0.0 {
+            (Vec3::X, Vec3::new(0.0, 0.0, -local_normal.y))
+        } else {
+            (Vec3::new(local_normal.z, 0.0, 0.0), Vec3::Y)
+        };
+
+        let p = local_point / self.size;
+        let uv = Vec2::new(p.dot(tangent), p.dot(bitangent)) + Vec2::splat(0.5);
+
+        (uv, tangent, bitangent)
+    }
+
     fn calculate_local_normal(&self, local_point: Vec3) -> Vec3 {
         let abs_point = local_point.abs();
         let max_component = abs_point.x.max(abs_point.y).max(abs_point.z);
@@ -64,10 +75,9 @@ impl Cube {
     }
 }
 
-impl Primitive for Cube {
-    fn hit(&self, ray: &Ray) -> Option<Intersection> {
-        let local_ray = self.transform_ray_to_local(ray);
-
+impl Cube {
+    // Distances where the line of the ray enters and leaves the cube
+    fn slabs(&self, local_ray: &Ray) -> Option<(f32, f32)> {
         let half = self.size / 2.0;
         let min = Vec3::new(-half, -half, -half);
         let max = Vec3::new(half, half, half);
@@ -88,18 +98,10 @@ impl Primitive for Cube {
         let tmin = t1.min(t2).max(t3.min(t4)).max(t5.min(t6));
         let tmax = t1.max(t2).min(t3.max(t4)).min(t5.max(t6));
 
-        if tmax < 0.0 || tmin > tmax {
-            return None;
-        }
-
-        let t = if tmin > 0.001 {
-            tmin
-        } else if tmax > 0.001 {
-            tmax
-        } else {
-            return None;
-        };
+        (tmin <= tmax).then_some((tmin, tmax))
+    }
 
+    fn surface_hit(&self, ray: &Ray, local_ray: &Ray, t: f32) -> Intersection {
         let local_point = local_ray.origin + t * local_ray.direction;
 
         let local_normal = self.calculate_local_normal(local_point);
@@ -107,13 +109,108 @@ impl Primitive for Cube {
         let world_point = self.transform_point_to_world(local_point);
         let world_normal = self.transform_normal_to_world(local_normal);
 
-        let world_distance = (world_point - ray.origin).length();
+        // Signed, so that crossings behind the ray's start stay behind it
+        let world_distance = (world_point - ray.origin).length() * t.signum();
 
-        Some(Intersection::new(
+        let (uv, tangent, bitangent) = self.calculate_local_uv(local_point, local_normal);
+
+        Intersection::new(
             world_distance,
             world_point,
             world_normal,
             self.material.clone(),
-        ))
+        )
+        .with_uv(uv)
+        .with_tangents(
+            self.rotation_matrix * tangent,
+            self.rotation_matrix * bitangent,
+        )
+    }
+}
+
+impl Primitive for Cube {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let local_ray = self.transform_ray_to_local(ray);
+        let (tmin, tmax) = self.slabs(&local_ray)?;
+
+        let t = if tmin > 0.001 {
+            tmin
+        } else if tmax > 0.001 {
+            tmax
+        } else {
+            return None;
+        };
+
+        Some(self.surface_hit(ray, &local_ray, t))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let half = Vec3::splat(self.size.abs() / 2.0);
+        Some(Aabb::new(-half, half).transform(self.rotation_matrix, self.center))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let local_ray = self.transform_ray_to_local(ray);
+        let Some((tmin, tmax)) = self.slabs(&local_ray) else {
+            return Vec::new();
+        };
+
+        intervals_between(
+            vec![
+                self.surface_hit(ray, &local_ray, tmin),
+                self.surface_hit(ray, &local_ray, tmax),
+            ],
+            |_| true,
+        )
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        let half = self.size / 2.0;
+        let face = fastrand::usize(0..6);
+        let axis = face / 2;
+        let sign = if face % 2 == 0 { 1.0 } else { -1.0 };
+
+        let mut local_point = Vec3::new(
+            (fastrand::f32() * 2.0 - 1.0) * half,
+            (fastrand::f32() * 2.0 - 1.0) * half,
+            (fastrand::f32() * 2.0 - 1.0) * half,
+        );
+        local_point[axis] = sign * half;
+
+        let mut local_normal = Vec3::ZERO;
+        local_normal[axis] = sign;
+
+        let point = self.transform_point_to_world(local_point);
+        let normal = self.transform_normal_to_world(local_normal);
+        let pdf_area = 1.0 / (6.0 * self.size * self.size);
+        let pdf = sampling::area_to_solid_angle(pdf_area, reference, point, normal);
+
+        (pdf > 0.0).then_some(EmissionSample {
+            point,
+            normal,
+            radiance: self.material.emission,
+            pdf,
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        let pdf_area = 1.0 / (6.0 * self.size * self.size);
+        sampling::area_to_solid_angle(pdf_area, reference, hit.point, hit.normal)
     }
 }
@@ -0,0 +1,125 @@
+use crate::geometry::revolution::{self, SurfaceHit};
+use crate::geometry::{EmissionSample, Interval, Primitive};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, Transform, euler_rotation, sampling};
+use glam::Vec3;
+use std::f32::consts::PI;
+
+// Capped cylinder standing on its local Y axis, centered on `center`
+pub struct Cylinder {
+    pub center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub rotation: Vec3,
+    pub material: Material,
+    transform: Transform,
+}
+
+impl Cylinder {
+    pub fn new(center: Vec3, radius: f32, height: f32, rotation: Vec3, material: Material) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            rotation,
+            material,
+            transform: Transform::new(center, euler_rotation(rotation), Vec3::ONE),
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        2.0 * PI * self.radius * (self.height + self.radius)
+    }
+
+    // Every place the line of the ray crosses the surface, behind its start
+    // too
+    fn crossings(&self, ray: &Ray) -> Vec<SurfaceHit> {
+        let (o, d) = (ray.origin, ray.direction);
+        let a = d.x * d.x + d.z * d.z;
+        let b = 2.0 * (o.x * d.x + o.z * d.z);
+        let c = o.x * o.x + o.z * o.z - self.radius * self.radius;
+        let half = self.height / 2.0;
+
+        let sides = revolution::quadratic(a, b, c)
+            .into_iter()
+            .flat_map(|(t0, t1)| [t0, t1])
+            .map(|t| (t, o + t * d))
+            .filter(|(_, point)| point.y.abs() <= half)
+            .map(|(t, point)| {
+                let normal = Vec3::new(point.x, 0.0, point.z) / self.radius;
+                SurfaceHit::side(t, point, normal, point.y / self.height + 0.5)
+            });
+
+        sides
+            .chain(revolution::cap(ray, half, self.radius, 1.0))
+            .chain(revolution::cap(ray, -half, self.radius, -1.0))
+            .collect()
+    }
+
+    fn sample_surface(&self) -> (Vec3, Vec3) {
+        let half = self.height / 2.0;
+        let side = self.height / (self.height + self.radius);
+
+        if fastrand::f32() < side {
+            let phi = 2.0 * PI * fastrand::f32();
+            let normal = Vec3::new(phi.sin(), 0.0, -phi.cos());
+            let y = (fastrand::f32() * 2.0 - 1.0) * half;
+            (normal * self.radius + Vec3::Y * y, normal)
+        } else if fastrand::bool() {
+            revolution::sample_cap(half, self.radius, 1.0)
+        } else {
+            revolution::sample_cap(-half, self.radius, -1.0)
+        }
+    }
+}
+
+impl Primitive for Cylinder {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, _) = self.transform.inverse_ray(ray)?;
+        let hit = revolution::closest(self.crossings(&local_ray))?;
+        Some(hit.to_world(&self.transform, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, self.height / 2.0, self.radius).abs();
+        Some(self.transform.aabb(&Aabb::new(-extent, extent)))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((local_ray, _)) = self.transform.inverse_ray(ray) else {
+            return Vec::new();
+        };
+
+        revolution::convex_intervals(self.crossings(&local_ray), &self.transform, &self.material)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        revolution::emission_sample(
+            &self.transform,
+            self.sample_surface(),
+            self.area(),
+            &self.material,
+            reference,
+        )
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, hit.normal)
+    }
+}
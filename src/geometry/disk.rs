@@ -0,0 +1,137 @@
+use crate::geometry::{EmissionSample, Primitive};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, euler_rotation, sampling};
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone)]
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: Material,
+    // In-plane axes that u is measured around
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl Disk {
+    // Faces +Y before the rotation, with UVs laid out like the top cap of a
+    // cylinder given the same rotation
+    pub fn new(center: Vec3, radius: f32, rotation: Vec3, material: Material) -> Self {
+        let rotation_matrix = euler_rotation(rotation);
+
+        Self {
+            center,
+            normal: rotation_matrix * Vec3::Y,
+            radius,
+            material,
+            tangent: rotation_matrix * -Vec3::Z,
+            bitangent: rotation_matrix * Vec3::X,
+        }
+    }
+
+    pub fn facing(center: Vec3, normal: Vec3, radius: f32, material: Material) -> Self {
+        let normal = normal.normalize();
+        let (tangent, bitangent) = sampling::orthonormal_basis(normal);
+
+        Self {
+            center,
+            normal,
+            radius,
+            material,
+            tangent,
+            bitangent,
+        }
+    }
+
+    // Lights only shine from the side the normal faces
+    fn faces(&self, reference: Vec3) -> bool {
+        (reference - self.center).dot(self.normal) > 0.0
+    }
+
+    fn front_material(&self, denominator: f32) -> Material {
+        let mut material = self.material.clone();
+        if denominator > 0.0 {
+            material.emission = Vec3::ZERO;
+        }
+        material
+    }
+
+    pub fn area(&self) -> f32 {
+        PI * self.radius * self.radius
+    }
+}
+
+impl Primitive for Disk {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let denominator = ray.direction.dot(self.normal);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.center - ray.origin).dot(self.normal) / denominator;
+        if t <= 0.001 {
+            return None;
+        }
+
+        let point = ray.origin + t * ray.direction;
+        let local = point - self.center;
+        let distance = local.length();
+        if distance > self.radius {
+            return None;
+        }
+
+        let (tangent, bitangent) = (self.tangent, self.bitangent);
+        let phi = local.dot(bitangent).atan2(local.dot(tangent));
+        let uv = Vec2::new(phi / (2.0 * PI) + 0.5, distance / self.radius);
+
+        // u runs around the center, v outwards
+        let radial = (local / distance).normalize_or(tangent);
+        let around = -phi.sin() * tangent + phi.cos() * bitangent;
+
+        Some(
+            Intersection::new(t, point, self.normal, self.front_material(denominator))
+                .with_uv(uv)
+                .with_tangents(around, radial),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Extent of a circle along each axis is radius * sin(angle to that axis)
+        let extent = (Vec3::ONE - self.normal * self.normal).max(Vec3::ZERO);
+        let extent = Vec3::new(extent.x.sqrt(), extent.y.sqrt(), extent.z.sqrt()) * self.radius;
+
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() || !self.faces(reference) {
+            return None;
+        }
+
+        let offset =
+            sampling::sample_concentric_disk(fastrand::f32(), fastrand::f32()) * self.radius;
+        let point = self.center + self.tangent * offset.x + self.bitangent * offset.y;
+        let pdf = sampling::area_to_solid_angle(1.0 / self.area(), reference, point, self.normal);
+
+        (pdf > 0.0).then_some(EmissionSample {
+            point,
+            normal: self.normal,
+            radiance: self.material.emission,
+            pdf,
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() || !self.faces(reference) {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, self.normal)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::geometry::csg::{self, CsgOperation};
+use crate::geometry::{Bvh, Interval, Primitive};
+use crate::math::{Aabb, Intersection, Ray};
+
+// A named set of primitives with its own BVH. Instances share one group
+// through an `Arc` and place it with a `Transformed` wrapper. Emissive
+// members are not sampled as lights.
+pub struct Group {
+    pub name: String,
+    objects: Vec<Box<dyn Primitive>>,
+    bvh: Bvh,
+    unbounded: Vec<usize>,
+}
+
+impl Group {
+    pub fn new(name: impl Into<String>, objects: Vec<Box<dyn Primitive>>) -> Self {
+        let bvh = Bvh::new(&objects);
+        let unbounded = objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.bounding_box().is_none())
+            .map(|(index, _)| index)
+            .collect();
+
+        Self {
+            name: name.into(),
+            objects,
+            bvh,
+            unbounded,
+        }
+    }
+
+    pub fn objects(&self) -> &[Box<dyn Primitive>] {
+        &self.objects
+    }
+}
+
+impl Primitive for Group {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let mut closest_hit = self.bvh.hit(&self.objects, ray).map(|(_, hit)| hit);
+
+        for &index in &self.unbounded {
+            if let Some(hit) = self.objects[index].hit(ray)
+                && hit.distance > 0.001
+                && closest_hit
+                    .as_ref()
+                    .is_none_or(|closest| hit.distance < closest.distance)
+            {
+                closest_hit = Some(hit);
+            }
+        }
+
+        closest_hit
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.unbounded.is_empty() {
+            self.bvh.bounds()
+        } else {
+            None
+        }
+    }
+
+    fn is_solid(&self) -> bool {
+        self.objects.iter().all(|object| object.is_solid())
+    }
+
+    // Members act as their union, so overlapping members nest rather than
+    // cancel out
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let inv_dir = ray.direction.recip();
+        self.objects
+            .iter()
+            .filter(|object| {
+                object
+                    .bounding_box()
+                    .is_none_or(|bounds| bounds.hit(ray, inv_dir, f32::INFINITY).is_some())
+            })
+            .map(|object| object.intervals(ray))
+            .reduce(|left, right| csg::combine(CsgOperation::Union, left, right))
+            .unwrap_or_default()
+    }
+}
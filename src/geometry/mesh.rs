@@ -0,0 +1,199 @@
+use crate::geometry::{Bvh, EmissionSample, EmissiveTriangles, Primitive, Triangle};
+use crate::loaders::{ObjFace, ObjModel};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, euler_rotation};
+use glam::Vec3;
+use std::collections::HashMap;
+
+pub struct Mesh {
+    pub position: Vec3,
+    pub scale: Vec3,
+    pub rotation: Vec3,
+    triangles: Vec<Triangle>,
+    bvh: Bvh,
+    emitters: EmissiveTriangles,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Triangle>, position: Vec3, scale: Vec3, rotation: Vec3) -> Self {
+        let bvh = Bvh::new(&triangles);
+        let emitters = EmissiveTriangles::new(&triangles);
+
+        Self {
+            position,
+            scale,
+            rotation,
+            triangles,
+            bvh,
+            emitters,
+        }
+    }
+
+    pub fn from_obj(
+        model: &ObjModel,
+        position: Vec3,
+        scale: Vec3,
+        rotation: Vec3,
+        material: &Material,
+        group_materials: &HashMap<String, Material>,
+        crease_angle: Option<f32>,
+    ) -> Self {
+        let rotation_matrix = euler_rotation(rotation);
+        let transform = |point: Vec3| position + rotation_matrix * (point * scale);
+        // Inverse transpose of the rotation and scale
+        let transform_normal =
+            |normal: Vec3| (rotation_matrix * (normal / scale)).normalize_or_zero();
+
+        let smooth_normals = crease_angle.map(|angle| Self::smooth_normals(model, angle));
+        let mut triangles = Vec::with_capacity(model.face_count());
+        let mut face_index = 0;
+
+        for group in &model.groups {
+            let group_material = group
+                .material
+                .as_ref()
+                .and_then(|name| group_materials.get(name))
+                .unwrap_or(material);
+
+            for face in &group.faces {
+                let corner_normals = smooth_normals.as_ref().map(|normals| normals[face_index]);
+                face_index += 1;
+
+                let [a, b, c] = face
+                    .vertices
+                    .map(|vertex| transform(model.positions[vertex.position]));
+
+                if (b - a).cross(c - a).length_squared() <= f32::EPSILON * f32::EPSILON {
+                    continue;
+                }
+
+                let mut triangle = Triangle::new(a, b, c, group_material.clone());
+                if let [Some(uv0), Some(uv1), Some(uv2)] = face.vertices.map(|vertex| vertex.uv) {
+                    triangle = triangle.with_uvs([model.uvs[uv0], model.uvs[uv1], model.uvs[uv2]]);
+                }
+                if let [Some(n0), Some(n1), Some(n2)] = face.vertices.map(|vertex| vertex.normal) {
+                    triangle = triangle.with_normals(
+                        [model.normals[n0], model.normals[n1], model.normals[n2]]
+                            .map(transform_normal),
+                    );
+                } else if let Some(normals) = corner_normals {
+                    triangle = triangle.with_normals(normals.map(transform_normal));
+                }
+                if !model.colors.is_empty() {
+                    triangle = triangle
+                        .with_colors(face.vertices.map(|vertex| model.colors[vertex.position]));
+                }
+                triangles.push(triangle);
+            }
+        }
+
+        Self::new(triangles, position, scale, rotation)
+    }
+
+    // Per-corner normals averaged from the area weighted normals of the faces
+    // sharing each position, leaving out faces beyond the crease angle
+    fn smooth_normals(model: &ObjModel, crease_angle: f32) -> Vec<[Vec3; 3]> {
+        let faces: Vec<&ObjFace> = model.groups.iter().flat_map(|group| &group.faces).collect();
+        let face_normals: Vec<Vec3> = faces
+            .iter()
+            .map(|face| {
+                let [a, b, c] = face.vertices.map(|vertex| model.positions[vertex.position]);
+                (b - a).cross(c - a)
+            })
+            .collect();
+
+        let mut adjacent_faces = vec![Vec::new(); model.positions.len()];
+        for (index, face) in faces.iter().enumerate() {
+            for vertex in face.vertices {
+                adjacent_faces[vertex.position].push(index);
+            }
+        }
+
+        let min_cos = crease_angle.to_radians().cos();
+        faces
+            .iter()
+            .zip(&face_normals)
+            .map(|(face, normal)| {
+                let direction = normal.normalize_or_zero();
+                face.vertices.map(|vertex| {
+                    adjacent_faces[vertex.position]
+                        .iter()
+                        .map(|&index| face_normals[index])
+                        .filter(|other| other.normalize_or_zero().dot(direction) >= min_cos)
+                        .sum::<Vec3>()
+                        .normalize_or(direction)
+                })
+            })
+            .collect()
+    }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        &self.triangles
+    }
+}
+
+impl Primitive for Mesh {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        self.bvh.hit(&self.triangles, ray).map(|(_, hit)| hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounds()
+    }
+
+    fn is_emissive(&self) -> bool {
+        !self.emitters.is_empty()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        self.emitters.sample(&self.triangles, reference)
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        self.emitters.pdf(reference, hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two faces meeting along the X axis at a right angle, one facing +Y and
+    // the other +Z
+    fn fold() -> ObjModel {
+        ObjModel::parse("v 0 0 0\nv 1 0 0\nv 1 0 -1\nv 0 1 0\nf 1 2 3\nf 1 2 4\n").unwrap()
+    }
+
+    fn shared_corner_normals(crease_angle: f32) -> [Vec3; 2] {
+        let normals = Mesh::smooth_normals(&fold(), crease_angle);
+        // Position 0 is the first corner of both faces
+        [normals[0][0], normals[1][0]]
+    }
+
+    #[test]
+    fn crease_angle_splits_sharp_edges() {
+        assert_eq!(shared_corner_normals(60.0), [Vec3::Y, Vec3::Z]);
+
+        let smooth = Vec3::new(0.0, 1.0, 1.0).normalize();
+        for normal in shared_corner_normals(120.0) {
+            assert!(normal.abs_diff_eq(smooth, 1e-6));
+        }
+    }
+
+    #[test]
+    fn file_normals_win_over_smoothing() {
+        let model =
+            ObjModel::parse("v 0 0 0\nv 1 0 0\nv 0 0 -1\nvn 1 0 0\nf 1//1 2//1 3//1\n").unwrap();
+        let mesh = Mesh::from_obj(
+            &model,
+            Vec3::ZERO,
+            Vec3::ONE,
+            Vec3::ZERO,
+            &Material::new(Vec3::ONE, 0.0, 1.0),
+            &HashMap::new(),
+            Some(180.0),
+        );
+
+        assert_eq!(mesh.triangles()[0].normals, Some([Vec3::X; 3]));
+    }
+}
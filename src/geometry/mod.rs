@@ -1,13 +1,36 @@
+pub mod bvh;
+pub mod capsule;
+pub mod cone;
+pub mod csg;
 pub mod cube;
+pub mod cylinder;
+pub mod disk;
+pub mod group;
+pub mod mesh;
 pub mod plane;
 pub mod primitive;
 pub mod pyramid;
+pub mod rectangle;
+mod revolution;
 pub mod sphere;
+pub mod torus;
+pub mod transformed;
 pub mod triangle;
 
+pub use bvh::Bvh;
+pub use capsule::Capsule;
+pub use cone::Cone;
+pub use csg::{Csg, CsgOperation};
 pub use cube::Cube;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use group::Group;
+pub use mesh::Mesh;
 pub use plane::Plane;
-pub use primitive::Primitive;
+pub use primitive::{EmissionSample, Interval, Primitive, crossing_intervals, intervals_between};
 pub use pyramid::Pyramid;
+pub use rectangle::Rectangle;
 pub use sphere::Sphere;
-pub use triangle::Triangle;
+pub use torus::Torus;
+pub use transformed::Transformed;
+pub use triangle::{EmissiveTriangles, Triangle};
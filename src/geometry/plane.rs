@@ -1,7 +1,7 @@
 use crate::geometry::Primitive;
 use crate::materials::Material;
-use crate::math::{Intersection, Ray};
-use glam::Vec3;
+use crate::math::{Aabb, Intersection, Ray, sampling};
+use glam::{Vec2, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct Plane {
@@ -32,14 +32,23 @@ impl Primitive for Plane {
 
         if t > 0.001 {
             let point = ray.origin + t * ray.direction;
-            Some(Intersection::new(
-                t,
-                point,
-                self.normal,
-                self.material.clone(),
-            ))
+
+            // One UV unit per scene unit, measured from `point`
+            let (tangent, bitangent) = sampling::orthonormal_basis(self.normal);
+            let local = point - self.point;
+            let uv = Vec2::new(local.dot(tangent), local.dot(bitangent));
+
+            Some(
+                Intersection::new(t, point, self.normal, self.material.clone())
+                    .with_uv(uv)
+                    .with_tangents(tangent, bitangent),
+            )
         } else {
             None
         }
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
 }
@@ -1,5 +1,382 @@
-use crate::math::{Intersection, Ray};
+use crate::math::{Aabb, Intersection, Ray};
+use glam::Vec3;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy)]
+pub struct EmissionSample {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub radiance: Vec3,
+    pub pdf: f32,
+}
+
+// Stretch of a ray inside a solid. A missing entry means the ray starts
+// inside, a missing exit that it never leaves.
+#[derive(Debug, Clone)]
+pub struct Interval {
+    pub enter: Option<Intersection>,
+    pub exit: Option<Intersection>,
+}
+
+impl Interval {
+    pub fn map(self, mut f: impl FnMut(Intersection) -> Intersection) -> Self {
+        Self {
+            enter: self.enter.map(&mut f),
+            exit: self.exit.map(&mut f),
+        }
+    }
+}
+
+// Stretches of the ray inside a closed shape, from every place the whole line
+// of the ray crosses its surface in order along it, behind its start too.
+// `inside` tells whether the gap around a distance lies in the shape, so a
+// ray that only touches the surface doesn't turn inside out. Only the parts
+// past the ray's start are kept.
+pub fn intervals_between(
+    crossings: Vec<Intersection>,
+    inside: impl Fn(f32) -> bool,
+) -> Vec<Interval> {
+    let mut intervals: Vec<Interval> = Vec::new();
+    let mut crossings = crossings.into_iter();
+    let Some(mut near) = crossings.next() else {
+        return intervals;
+    };
+
+    for far in crossings {
+        if far.distance > 0.001
+            && far.distance > near.distance
+            && inside((near.distance + far.distance) / 2.0)
+        {
+            match intervals.last_mut() {
+                Some(Interval {
+                    exit: Some(exit), ..
+                }) if exit.distance == near.distance => *exit = far.clone(),
+                _ => intervals.push(Interval {
+                    enter: (near.distance > 0.001).then_some(near),
+                    exit: Some(far.clone()),
+                }),
+            }
+        }
+        near = far;
+    }
+
+    intervals
+}
+
+const MAX_CROSSINGS: usize = 256;
+
+// Follows the ray through every surface crossing that `hit` reports, and
+// counts how deep inside the shape each stretch lies from the side the
+// normals face. Overlapping parts, such as the members of a group, nest
+// rather than cancel out.
+pub fn crossing_intervals<P: Primitive + ?Sized>(primitive: &P, ray: &Ray) -> Vec<Interval> {
+    let mut crossings = Vec::new();
+    let mut origin = ray.origin;
+    let mut travelled = 0.0;
+    while crossings.len() < MAX_CROSSINGS
+        && let Some(mut hit) = primitive.hit(&Ray::new(origin, ray.direction))
+    {
+        origin = hit.point;
+        travelled += hit.distance;
+        hit.distance = travelled;
+        crossings.push(hit);
+    }
+
+    let steps: Vec<i32> = crossings
+        .iter()
+        .map(|hit| {
+            if ray.direction.dot(hit.normal) < 0.0 {
+                1
+            } else {
+                -1
+            }
+        })
+        .collect();
+
+    // Start deep enough that leaving every surface brings the depth to zero
+    let mut depth = -steps
+        .iter()
+        .scan(0, |depth, step| {
+            *depth += step;
+            Some(*depth)
+        })
+        .min()
+        .unwrap_or(0)
+        .min(0);
+
+    let mut intervals = Vec::new();
+    let mut inside = depth > 0;
+    let mut enter = None;
+    for (hit, step) in crossings.into_iter().zip(steps) {
+        depth += step;
+        if depth > 0 && !inside {
+            enter = Some(hit);
+            inside = true;
+        } else if depth <= 0 && inside {
+            intervals.push(Interval {
+                enter: enter.take(),
+                exit: Some(hit),
+            });
+            inside = false;
+        }
+    }
+    if inside {
+        intervals.push(Interval { enter, exit: None });
+    }
+
+    intervals
+}
 
 pub trait Primitive: Send + Sync {
     fn hit(&self, ray: &Ray) -> Option<Intersection>;
+
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    fn sample_emission(&self, _reference: Vec3) -> Option<EmissionSample> {
+        None
+    }
+
+    fn emission_pdf(&self, _reference: Vec3, _hit: &Intersection) -> f32 {
+        0.0
+    }
+
+    // Whether the shape encloses a volume and so can be a CSG operand. Only
+    // shapes that are closed by construction opt in.
+    fn is_solid(&self) -> bool {
+        false
+    }
+
+    // Stretches of the ray inside the shape in order along it. Solids find
+    // them from their own surface equations; this fallback follows `hit`
+    // from crossing to crossing and relies on normals facing out of the shape.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        crossing_intervals(self, ray)
+    }
+}
+
+impl<P: Primitive + ?Sized> Primitive for Box<P> {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        (**self).hit(ray)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn is_emissive(&self) -> bool {
+        (**self).is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        (**self).sample_emission(reference)
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        (**self).emission_pdf(reference, hit)
+    }
+
+    fn is_solid(&self) -> bool {
+        (**self).is_solid()
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        (**self).intervals(ray)
+    }
+}
+
+impl<P: Primitive + ?Sized> Primitive for Arc<P> {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        (**self).hit(ray)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn is_emissive(&self) -> bool {
+        (**self).is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        (**self).sample_emission(reference)
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        (**self).emission_pdf(reference, hit)
+    }
+
+    fn is_solid(&self) -> bool {
+        (**self).is_solid()
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        (**self).intervals(ray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Capsule, Cone, Cube, Cylinder, Disk, Pyramid, Rectangle, Sphere, Torus};
+    use crate::materials::Material;
+    use crate::math::sampling;
+
+    fn emitter() -> Material {
+        let mut material = Material::new(Vec3::ZERO, 0.0, 1.0);
+        material.emission = Vec3::ONE;
+        material
+    }
+
+    // Both one-sided lights hang at y = 1 facing down
+    fn flat_lights() -> Vec<Box<dyn Primitive>> {
+        vec![
+            Box::new(Rectangle::new(Vec3::Y, -Vec3::Y, 1.0, 1.0, emitter())),
+            Box::new(Disk::facing(Vec3::Y, -Vec3::Y, 0.5, emitter())),
+        ]
+    }
+
+    #[test]
+    fn flat_lights_are_dark_from_behind() {
+        let below = Vec3::new(0.2, 0.0, 0.1);
+        let above = Vec3::new(0.2, 2.0, 0.1);
+
+        for light in flat_lights() {
+            assert!(light.sample_emission(below).is_some());
+            assert!(light.sample_emission(above).is_none());
+
+            let front = light.hit(&Ray::new(below, Vec3::Y)).unwrap();
+            let back = light.hit(&Ray::new(above, -Vec3::Y)).unwrap();
+            assert_eq!(front.material.emission, Vec3::ONE);
+            assert_eq!(back.material.emission, Vec3::ZERO);
+            assert!(light.emission_pdf(below, &front) > 0.0);
+            assert_eq!(light.emission_pdf(above, &back), 0.0);
+        }
+    }
+
+    #[test]
+    fn sampled_pdf_matches_the_pdf_of_the_traced_hit() {
+        fastrand::seed(7);
+        let mut lights = flat_lights();
+        lights.push(Box::new(Sphere::new(Vec3::Y, 0.5, emitter())));
+
+        let references = [
+            Vec3::new(0.2, 0.0, 0.1),
+            Vec3::new(1.5, 0.3, -0.7),
+            Vec3::new(-2.0, 0.9, 0.4),
+            // Inside the sphere, where it switches to area sampling
+            Vec3::new(0.1, 1.2, 0.0),
+        ];
+
+        for light in &lights {
+            for &reference in &references {
+                for _ in 0..32 {
+                    let Some(sample) = light.sample_emission(reference) else {
+                        continue;
+                    };
+                    let direction = (sample.point - reference).normalize();
+                    let hit = light.hit(&Ray::new(reference, direction)).unwrap();
+                    let pdf = light.emission_pdf(reference, &hit);
+
+                    assert!(hit.point.abs_diff_eq(sample.point, 1e-3));
+                    assert!(
+                        (pdf - sample.pdf).abs() <= 1e-3 * sample.pdf,
+                        "sampled pdf {} but traced pdf {} from {}",
+                        sample.pdf,
+                        pdf,
+                        reference
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sphere_light_pdf_integrates_to_one() {
+        let light = Sphere::new(Vec3::Y, 0.5, emitter());
+        let steps = 512;
+        let solid_angle = 4.0 * std::f32::consts::PI / (steps * steps) as f32;
+
+        for reference in [Vec3::new(0.3, -1.0, 0.2), Vec3::new(0.1, 1.2, 0.0)] {
+            let mut total = 0.0_f64;
+            for i in 0..steps {
+                for j in 0..steps {
+                    let direction = sampling::sample_uniform_sphere(
+                        (i as f32 + 0.5) / steps as f32,
+                        (j as f32 + 0.5) / steps as f32,
+                    );
+                    if let Some(hit) = light.hit(&Ray::new(reference, direction)) {
+                        total += (light.emission_pdf(reference, &hit) * solid_angle) as f64;
+                    }
+                }
+            }
+
+            assert!((total - 1.0).abs() < 1e-2, "pdf integrates to {}", total);
+        }
+    }
+
+    #[test]
+    fn solid_intervals_match_the_crossing_fallback() {
+        fastrand::seed(11);
+        let material = Material::new(Vec3::splat(0.8), 0.0, 0.5);
+        let rotation = Vec3::new(20.0, 35.0, -10.0);
+        let solids: Vec<Box<dyn Primitive>> = vec![
+            Box::new(Sphere::new(Vec3::ZERO, 1.0, material.clone())),
+            Box::new(Cube::new(Vec3::ZERO, rotation, 1.5, material.clone())),
+            Box::new(Pyramid::new(-Vec3::Y, 1.5, 2.0, rotation, material.clone())),
+            Box::new(Cylinder::new(
+                Vec3::ZERO,
+                0.8,
+                1.5,
+                rotation,
+                material.clone(),
+            )),
+            Box::new(Cone::new(-Vec3::Y, 1.0, 2.0, rotation, material.clone())),
+            Box::new(Capsule::new(
+                Vec3::ZERO,
+                0.5,
+                1.0,
+                rotation,
+                material.clone(),
+            )),
+            Box::new(Torus::new(Vec3::ZERO, 1.0, 0.4, rotation, material)),
+        ];
+
+        for solid in &solids {
+            assert!(solid.is_solid());
+            for _ in 0..2000 {
+                let random = || Vec3::new(fastrand::f32(), fastrand::f32(), fastrand::f32());
+                // Origins both outside and inside the shapes
+                let origin = random() * 4.0 - Vec3::splat(2.0);
+                let direction = (random() * 2.0 - Vec3::ONE).normalize_or(Vec3::X);
+                let ray = Ray::new(origin, direction);
+
+                let distances = |intervals: Vec<Interval>| -> Vec<(Option<f32>, Option<f32>)> {
+                    intervals
+                        .into_iter()
+                        .map(|interval| {
+                            (
+                                interval.enter.map(|hit| hit.distance),
+                                interval.exit.map(|hit| hit.distance),
+                            )
+                        })
+                        .collect()
+                };
+                let analytic = distances(solid.intervals(&ray));
+                let fallback = distances(crossing_intervals(solid.as_ref(), &ray));
+
+                assert_eq!(analytic.len(), fallback.len(), "{:?}", ray);
+                for (a, b) in analytic.iter().zip(&fallback) {
+                    let close = |a: Option<f32>, b: Option<f32>| match (a, b) {
+                        (Some(a), Some(b)) => (a - b).abs() < 1e-3,
+                        (a, b) => a == b,
+                    };
+                    assert!(close(a.0, b.0) && close(a.1, b.1), "{:?} {:?}", a, b);
+                }
+            }
+        }
+    }
 }
@@ -1,7 +1,9 @@
-use crate::geometry::{Primitive, Triangle};
+use crate::geometry::{
+    Bvh, EmissionSample, EmissiveTriangles, Interval, Primitive, Triangle, intervals_between,
+};
 use crate::materials::Material;
-use crate::math::{Intersection, Ray};
-use glam::{Mat3, Vec3};
+use crate::math::{Aabb, Intersection, Ray, euler_rotation};
+use glam::{Vec2, Vec3};
 
 pub struct Pyramid {
     pub base_center: Vec3,
@@ -10,6 +12,8 @@ pub struct Pyramid {
     pub rotation: Vec3,
     pub material: Material,
     triangles: Vec<Triangle>,
+    bvh: Bvh,
+    emitters: EmissiveTriangles,
 }
 
 impl Pyramid {
@@ -31,7 +35,7 @@ impl Pyramid {
 
         let apex = Vec3::new(0.0, height, 0.0);
 
-        let rotation_matrix = Self::create_rotation_matrix(rotation);
+        let rotation_matrix = euler_rotation(rotation);
 
         let rotated_base: Vec<Vec3> = base_points
             .iter()
@@ -40,31 +44,49 @@ impl Pyramid {
 
         let rotated_apex = base_center + rotation_matrix * apex;
 
+        // The base maps to the full UV square, each side to a triangle
+        // standing on the bottom edge
+        let base_uvs = base_points.map(|point| Vec2::new(point.x, point.z) / base_size + 0.5);
+        let side_uvs = [Vec2::ZERO, Vec2::new(0.5, 1.0), Vec2::X];
+
+        // Wound so that every face normal points out of the pyramid
         let mut triangles = Vec::new();
 
-        triangles.push(Triangle::new(
-            rotated_base[0],
-            rotated_base[2],
-            rotated_base[1],
-            material.clone(),
-        ));
-        triangles.push(Triangle::new(
-            rotated_base[0],
-            rotated_base[3],
-            rotated_base[2],
-            material.clone(),
-        ));
+        triangles.push(
+            Triangle::new(
+                rotated_base[0],
+                rotated_base[1],
+                rotated_base[2],
+                material.clone(),
+            )
+            .with_uvs([base_uvs[0], base_uvs[1], base_uvs[2]]),
+        );
+        triangles.push(
+            Triangle::new(
+                rotated_base[0],
+                rotated_base[2],
+                rotated_base[3],
+                material.clone(),
+            )
+            .with_uvs([base_uvs[0], base_uvs[2], base_uvs[3]]),
+        );
 
         for i in 0..4 {
             let next_i = (i + 1) % 4;
-            triangles.push(Triangle::new(
-                rotated_base[i],
-                rotated_base[next_i],
-                rotated_apex,
-                material.clone(),
-            ));
+            triangles.push(
+                Triangle::new(
+                    rotated_base[i],
+                    rotated_apex,
+                    rotated_base[next_i],
+                    material.clone(),
+                )
+                .with_uvs(side_uvs),
+            );
         }
 
+        let bvh = Bvh::new(&triangles);
+        let emitters = EmissiveTriangles::new(&triangles);
+
         Self {
             base_center,
             base_size,
@@ -72,35 +94,60 @@ impl Pyramid {
             rotation,
             material,
             triangles,
+            bvh,
+            emitters,
         }
     }
+}
 
-    fn create_rotation_matrix(rotation: Vec3) -> Mat3 {
-        let rot_rad = rotation * std::f32::consts::PI / 180.0;
+impl Primitive for Pyramid {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        self.bvh.hit(&self.triangles, ray).map(|(_, hit)| hit)
+    }
 
-        let rot_x = Mat3::from_rotation_x(rot_rad.x);
-        let rot_y = Mat3::from_rotation_y(rot_rad.y);
-        let rot_z = Mat3::from_rotation_z(rot_rad.z);
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounds()
+    }
 
-        rot_z * rot_y * rot_x
+    fn is_solid(&self) -> bool {
+        true
     }
-}
 
-impl Primitive for Pyramid {
-    fn hit(&self, ray: &Ray) -> Option<Intersection> {
-        let mut closest_hit: Option<Intersection> = None;
-        let mut closest_distance = f32::INFINITY;
-
-        for triangle in &self.triangles {
-            if let Some(hit) = triangle.hit(ray)
-                && hit.distance < closest_distance
-                && hit.distance > 0.001
-            {
-                closest_distance = hit.distance;
-                closest_hit = Some(hit);
+    // Convex, so the line of the ray enters at its first crossing and leaves
+    // at its last. Crossings behind the start are found along the reversed
+    // ray.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let reversed = Ray::new(ray.origin, -ray.direction);
+        let behind = self.triangles.iter().filter_map(|triangle| {
+            let mut hit = triangle.hit(&reversed)?;
+            hit.distance = -hit.distance;
+            Some(hit)
+        });
+        let mut crossings: Vec<Intersection> = self
+            .triangles
+            .iter()
+            .filter_map(|triangle| triangle.hit(ray))
+            .chain(behind)
+            .collect();
+        crossings.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        match (crossings.first(), crossings.last()) {
+            (Some(first), Some(last)) => {
+                intervals_between(vec![first.clone(), last.clone()], |_| true)
             }
+            _ => Vec::new(),
         }
+    }
+
+    fn is_emissive(&self) -> bool {
+        !self.emitters.is_empty()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        self.emitters.sample(&self.triangles, reference)
+    }
 
-        closest_hit
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        self.emitters.pdf(reference, hit)
     }
 }
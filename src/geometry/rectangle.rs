@@ -0,0 +1,126 @@
+use crate::geometry::{EmissionSample, Primitive};
+use crate::materials::Material;
+use crate::math::{Aabb, Intersection, Ray, sampling};
+use glam::{Vec2, Vec3};
+
+// Flat rectangle centered on `center`, facing `normal`. `width` runs along
+// the horizontal tangent of the normal, `height` along the remaining axis.
+#[derive(Debug, Clone)]
+pub struct Rectangle {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub width: f32,
+    pub height: f32,
+    pub material: Material,
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl Rectangle {
+    pub fn new(center: Vec3, normal: Vec3, width: f32, height: f32, material: Material) -> Self {
+        let normal = normal.normalize();
+        let tangent = if normal.y.abs() > 0.999 {
+            Vec3::X
+        } else {
+            Vec3::Y.cross(normal).normalize()
+        };
+
+        Self {
+            center,
+            normal,
+            width,
+            height,
+            material,
+            tangent,
+            bitangent: normal.cross(tangent),
+        }
+    }
+
+    // Lights only shine from the side the normal faces
+    fn faces(&self, reference: Vec3) -> bool {
+        (reference - self.center).dot(self.normal) > 0.0
+    }
+
+    fn front_material(&self, denominator: f32) -> Material {
+        let mut material = self.material.clone();
+        if denominator > 0.0 {
+            material.emission = Vec3::ZERO;
+        }
+        material
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+}
+
+impl Primitive for Rectangle {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let denominator = ray.direction.dot(self.normal);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.center - ray.origin).dot(self.normal) / denominator;
+        if t <= 0.001 {
+            return None;
+        }
+
+        let point = ray.origin + t * ray.direction;
+        let local = point - self.center;
+        let u = local.dot(self.tangent) / self.width + 0.5;
+        let v = local.dot(self.bitangent) / self.height + 0.5;
+
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+
+        Some(
+            Intersection::new(t, point, self.normal, self.front_material(denominator))
+                .with_uv(Vec2::new(u, v))
+                .with_tangents(self.tangent, self.bitangent),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let half_u = self.tangent * self.width * 0.5;
+        let half_v = self.bitangent * self.height * 0.5;
+
+        Some(Aabb::from_points(&[
+            self.center - half_u - half_v,
+            self.center + half_u - half_v,
+            self.center + half_u + half_v,
+            self.center - half_u + half_v,
+        ]))
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() || !self.faces(reference) {
+            return None;
+        }
+
+        let point = self.center
+            + self.tangent * (fastrand::f32() - 0.5) * self.width
+            + self.bitangent * (fastrand::f32() - 0.5) * self.height;
+        let pdf = sampling::area_to_solid_angle(1.0 / self.area(), reference, point, self.normal);
+
+        (pdf > 0.0).then_some(EmissionSample {
+            point,
+            normal: self.normal,
+            radiance: self.material.emission,
+            pdf,
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() || !self.faces(reference) {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, self.normal)
+    }
+}
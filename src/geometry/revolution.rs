@@ -0,0 +1,153 @@
+use crate::geometry::{EmissionSample, Interval, intervals_between};
+use crate::materials::Material;
+use crate::math::{Intersection, Ray, Transform, sampling};
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+// Shared pieces of the shapes built around their local Y axis. u runs around
+// the axis starting behind it, as on spheres, and the hits are found in
+// object space before moving back through the shape's rigid transform.
+
+pub(crate) struct SurfaceHit {
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
+}
+
+impl SurfaceHit {
+    // Hit on a curved side, with v running up the profile
+    pub fn side(distance: f32, point: Vec3, normal: Vec3, v: f32) -> Self {
+        let tangent = around(point);
+        Self {
+            distance,
+            point,
+            normal,
+            uv: Vec2::new(azimuth(point), v),
+            tangent,
+            bitangent: tangent.cross(normal),
+        }
+    }
+
+    pub fn to_world(&self, transform: &Transform, material: &Material) -> Intersection {
+        let hit = Intersection::new(self.distance, self.point, self.normal, material.clone())
+            .with_uv(self.uv)
+            .with_tangents(self.tangent, self.bitangent);
+        transform.intersection_to_world(hit, 1.0)
+    }
+}
+
+pub(crate) fn azimuth(point: Vec3) -> f32 {
+    point.x.atan2(-point.z) / (2.0 * PI) + 0.5
+}
+
+// Direction of increasing azimuth
+pub(crate) fn around(point: Vec3) -> Vec3 {
+    Vec3::new(-point.z, 0.0, point.x).normalize_or(Vec3::X)
+}
+
+// Nearest of the crossings past the ray's start
+pub(crate) fn closest(crossings: impl IntoIterator<Item = SurfaceHit>) -> Option<SurfaceHit> {
+    crossings
+        .into_iter()
+        .filter(|hit| hit.distance > 0.001)
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+// Stretch of the ray inside a convex shape, which the whole line of the ray
+// enters at its first crossing and leaves at its last
+pub(crate) fn convex_intervals(
+    crossings: impl IntoIterator<Item = SurfaceHit>,
+    transform: &Transform,
+    material: &Material,
+) -> Vec<Interval> {
+    let mut crossings: Vec<SurfaceHit> = crossings.into_iter().collect();
+    crossings.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    let (Some(first), Some(last)) = (crossings.first(), crossings.last()) else {
+        return Vec::new();
+    };
+
+    intervals_between(
+        vec![
+            first.to_world(transform, material),
+            last.to_world(transform, material),
+        ],
+        |_| true,
+    )
+}
+
+// Flat cap at height `y` facing up or down, mapped like a disk: u around the
+// center and v outwards. Crossings behind the ray's start count too.
+pub(crate) fn cap(ray: &Ray, y: f32, radius: f32, facing: f32) -> Option<SurfaceHit> {
+    if ray.direction.y.abs() < 1e-8 {
+        return None;
+    }
+
+    let t = (y - ray.origin.y) / ray.direction.y;
+
+    let point = ray.origin + t * ray.direction;
+    let distance = point.x.hypot(point.z);
+    if distance > radius {
+        return None;
+    }
+
+    Some(SurfaceHit {
+        distance: t,
+        point,
+        normal: Vec3::new(0.0, facing, 0.0),
+        uv: Vec2::new(azimuth(point), distance / radius),
+        tangent: around(point),
+        bitangent: Vec3::new(point.x, 0.0, point.z).normalize_or(Vec3::Z),
+    })
+}
+
+// Roots of a t^2 + b t + c = 0 in increasing order
+pub(crate) fn quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return None;
+        }
+        let t = -c / b;
+        return Some((t, t));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrt_d = discriminant.sqrt();
+    let (t0, t1) = ((-b - sqrt_d) / (2.0 * a), (-b + sqrt_d) / (2.0 * a));
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+// Area light sample from a point and normal drawn uniformly over the surface
+// in object space
+pub(crate) fn emission_sample(
+    transform: &Transform,
+    (local_point, local_normal): (Vec3, Vec3),
+    area: f32,
+    material: &Material,
+    reference: Vec3,
+) -> Option<EmissionSample> {
+    let point = transform.point(local_point);
+    let normal = transform.normal(local_normal);
+    let pdf = sampling::area_to_solid_angle(1.0 / area, reference, point, normal);
+
+    (pdf > 0.0).then_some(EmissionSample {
+        point,
+        normal,
+        radiance: material.emission,
+        pdf,
+    })
+}
+
+pub(crate) fn sample_cap(y: f32, radius: f32, facing: f32) -> (Vec3, Vec3) {
+    let offset = sampling::sample_concentric_disk(fastrand::f32(), fastrand::f32()) * radius;
+    (
+        Vec3::new(offset.x, y, offset.y),
+        Vec3::new(0.0, facing, 0.0),
+    )
+}
@@ -1,8 +1,10 @@
-use crate::geometry::Primitive;
+use crate::geometry::{EmissionSample, Interval, Primitive, intervals_between};
 use crate::materials::Material;
+use crate::math::Aabb;
 use crate::math::Intersection;
 use crate::math::Ray;
-use glam::Vec3;
+use crate::math::sampling;
+use glam::{Vec2, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct Sphere {
@@ -21,8 +23,9 @@ impl Sphere {
     }
 }
 
-impl Primitive for Sphere {
-    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+impl Sphere {
+    // Both distances where the line of the ray crosses the sphere
+    fn roots(&self, ray: &Ray) -> Option<(f32, f32)> {
         let oc = ray.origin - self.center;
 
         let a = ray.direction.dot(ray.direction);
@@ -38,6 +41,30 @@ impl Primitive for Sphere {
         let sqrt_discriminant = discriminant.sqrt();
         let t1 = (-b - sqrt_discriminant) / (2.0 * a);
         let t2 = (-b + sqrt_discriminant) / (2.0 * a);
+        Some((t1, t2))
+    }
+
+    fn surface_hit(&self, ray: &Ray, t: f32) -> Intersection {
+        let point = ray.origin + t * ray.direction;
+        let normal = (point - self.center).normalize();
+
+        // Longitude around +Y starting at -Z, latitude from the bottom pole
+        let u = 0.5 + normal.x.atan2(-normal.z) / (2.0 * std::f32::consts::PI);
+        let v = 1.0 - normal.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+
+        // Longitude grows around +Y, latitude towards the top pole
+        let tangent = Vec3::new(-normal.z, 0.0, normal.x).normalize_or(Vec3::X);
+        let bitangent = tangent.cross(normal);
+
+        Intersection::new(t, point, normal, self.material.clone())
+            .with_uv(Vec2::new(u, v))
+            .with_tangents(tangent, bitangent)
+    }
+}
+
+impl Primitive for Sphere {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (t1, t2) = self.roots(ray)?;
 
         let t = if t1 > 0.001 {
             t1
@@ -47,9 +74,92 @@ impl Primitive for Sphere {
             return None;
         };
 
-        let point = ray.origin + t * ray.direction;
+        Some(self.surface_hit(ray, t))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::splat(self.radius.abs());
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((t1, t2)) = self.roots(ray) else {
+            return Vec::new();
+        };
+
+        intervals_between(
+            vec![self.surface_hit(ray, t1), self.surface_hit(ray, t2)],
+            |_| true,
+        )
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        let to_center = self.center - reference;
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius * self.radius;
+
+        if distance_squared <= radius_squared {
+            let normal = sampling::sample_uniform_sphere(fastrand::f32(), fastrand::f32());
+            let point = self.center + normal * self.radius;
+            let pdf_area = 1.0 / (4.0 * std::f32::consts::PI * radius_squared);
+
+            return Some(EmissionSample {
+                point,
+                normal,
+                radiance: self.material.emission,
+                pdf: sampling::area_to_solid_angle(pdf_area, reference, point, normal),
+            });
+        }
+
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).max(0.0).sqrt();
+        let local = sampling::sample_cone(fastrand::f32(), fastrand::f32(), cos_theta_max);
+        let direction = sampling::to_world(local, to_center.normalize());
+
+        let distance = distance_squared.sqrt();
+        let cos_theta = local.z;
+        let sin_theta_squared = 1.0 - cos_theta * cos_theta;
+        let along = distance * cos_theta
+            - (radius_squared - distance_squared * sin_theta_squared)
+                .max(0.0)
+                .sqrt();
+
+        let point = reference + direction * along;
         let normal = (point - self.center).normalize();
 
-        Some(Intersection::new(t, point, normal, self.material.clone()))
+        Some(EmissionSample {
+            point,
+            normal,
+            radiance: self.material.emission,
+            pdf: sampling::cone_pdf(cos_theta_max),
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        let distance_squared = (self.center - reference).length_squared();
+        let radius_squared = self.radius * self.radius;
+
+        if distance_squared <= radius_squared {
+            let pdf_area = 1.0 / (4.0 * std::f32::consts::PI * radius_squared);
+            return sampling::area_to_solid_angle(pdf_area, reference, hit.point, hit.normal);
+        }
+
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).max(0.0).sqrt();
+        sampling::cone_pdf(cos_theta_max)
     }
 }
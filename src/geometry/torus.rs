@@ -0,0 +1,257 @@
+use crate::geometry::revolution::{self, SurfaceHit};
+use crate::geometry::{EmissionSample, Interval, Primitive, intervals_between};
+use crate::materials::Material;
+use crate::math::polynomial::solve_quartic;
+use crate::math::{Aabb, Intersection, Ray, Transform, euler_rotation, sampling};
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+// Torus around the local Y axis: a tube of `minor_radius` swept along a
+// circle of `major_radius` in the XZ plane
+pub struct Torus {
+    pub center: Vec3,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+    pub rotation: Vec3,
+    pub material: Material,
+    transform: Transform,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        rotation: Vec3,
+        material: Material,
+    ) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            rotation,
+            material,
+            transform: Transform::new(center, euler_rotation(rotation), Vec3::ONE),
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        4.0 * PI * PI * self.major_radius * self.minor_radius
+    }
+
+    // u runs around the Y axis and v around the tube, starting on the outside
+    fn surface_hit(&self, distance: f32, point: Vec3) -> SurfaceHit {
+        let radial = Vec3::new(point.x, 0.0, point.z).normalize_or(Vec3::X);
+        let normal = (point - radial * self.major_radius).normalize_or(radial);
+        let theta = point.y.atan2(point.x.hypot(point.z) - self.major_radius);
+        let tangent = revolution::around(point);
+
+        SurfaceHit {
+            distance,
+            point,
+            normal,
+            uv: Vec2::new(revolution::azimuth(point), theta / (2.0 * PI) + 0.5),
+            tangent,
+            bitangent: tangent.cross(normal),
+        }
+    }
+
+    // Distances of every place the line of the ray crosses the surface in
+    // order, behind its start too. A ray grazing the tube gives a repeated
+    // root. It hits at the touching point, but one that passes within
+    // rounding error of the surface may either hit or miss there.
+    fn crossings(&self, ray: &Ray) -> Vec<f32> {
+        let (big_r, small_r) = (self.major_radius, self.minor_radius);
+
+        // Start the ray where it first could reach the torus, which keeps
+        // the quartic's coefficients small
+        let start = (-ray.origin.dot(ray.direction) - (big_r + small_r)).max(0.0);
+        let o = (ray.origin + start * ray.direction).as_dvec3();
+        let d = ray.direction.as_dvec3();
+        let (big_r, small_r) = (big_r as f64, small_r as f64);
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + z^2) along p = o + t d, |d| = 1
+        let e = o.dot(d);
+        let g = o.length_squared() + big_r * big_r - small_r * small_r;
+        let four_r2 = 4.0 * big_r * big_r;
+        let roots = solve_quartic(
+            1.0,
+            4.0 * e,
+            4.0 * e * e + 2.0 * g - four_r2 * (d.x * d.x + d.z * d.z),
+            4.0 * e * g - 2.0 * four_r2 * (o.x * d.x + o.z * d.z),
+            g * g - four_r2 * (o.x * o.x + o.z * o.z),
+        );
+
+        roots.into_iter().map(|root| start + root as f32).collect()
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        let from_ring = point.x.hypot(point.z) - self.major_radius;
+        from_ring * from_ring + point.y * point.y < self.minor_radius * self.minor_radius
+    }
+
+    fn sample_surface(&self) -> (Vec3, Vec3) {
+        let (big_r, small_r) = (self.major_radius, self.minor_radius);
+        let phi = 2.0 * PI * fastrand::f32();
+
+        // The outside of the tube has more area than the inside; pick the
+        // angle around the tube in proportion to its distance from the axis.
+        // Rejecting until a try is accepted keeps that density exact, and on
+        // average R / (R + r) of the tries are accepted
+        let theta = loop {
+            let theta = 2.0 * PI * fastrand::f32();
+            if fastrand::f32() * (big_r + small_r) <= big_r + small_r * theta.cos() {
+                break theta;
+            }
+        };
+
+        let radial = Vec3::new(phi.sin(), 0.0, -phi.cos());
+        let normal = radial * theta.cos() + Vec3::Y * theta.sin();
+        (radial * big_r + normal * small_r, normal)
+    }
+}
+
+impl Primitive for Torus {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, _) = self.transform.inverse_ray(ray)?;
+        let t = self
+            .crossings(&local_ray)
+            .into_iter()
+            .find(|&t| t > 0.001)?;
+        let hit = self.surface_hit(t, local_ray.origin + t * local_ray.direction);
+        Some(hit.to_world(&self.transform, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let outer = self.major_radius.abs() + self.minor_radius.abs();
+        let extent = Vec3::new(outer, self.minor_radius.abs(), outer);
+        Some(self.transform.aabb(&Aabb::new(-extent, extent)))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    // The ray can pass through the tube twice, and a repeated root where it
+    // grazes the tube separates two gaps on the same side of the surface
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((local_ray, _)) = self.transform.inverse_ray(ray) else {
+            return Vec::new();
+        };
+
+        let crossings = self
+            .crossings(&local_ray)
+            .into_iter()
+            .map(|t| {
+                self.surface_hit(t, local_ray.origin + t * local_ray.direction)
+                    .to_world(&self.transform, &self.material)
+            })
+            .collect();
+        intervals_between(crossings, |t| {
+            self.contains(local_ray.origin + t * local_ray.direction)
+        })
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        revolution::emission_sample(
+            &self.transform,
+            self.sample_surface(),
+            self.area(),
+            &self.material,
+            reference,
+        )
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, hit.normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Vec3::ZERO,
+            2.0,
+            0.5,
+            Vec3::ZERO,
+            Material::new(Vec3::ONE, 0.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn ray_through_the_hole_crosses_the_tube_twice() {
+        let intervals = torus().intervals(&Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X));
+        let distances: Vec<_> = intervals
+            .iter()
+            .map(|interval| {
+                let enter = interval.enter.as_ref().unwrap().distance;
+                let exit = interval.exit.as_ref().unwrap().distance;
+                (enter, exit)
+            })
+            .collect();
+
+        assert_eq!(distances.len(), 2);
+        for ((enter, exit), expected) in distances.into_iter().zip([(2.5, 3.5), (6.5, 7.5)]) {
+            assert!((enter - expected.0).abs() < 1e-4 && (exit - expected.1).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn grazing_ray_stays_outside_the_tube() {
+        // Touches the top of the tube on both sides of the hole
+        let ray = Ray::new(Vec3::new(-5.0, 0.5, 0.0), Vec3::X);
+        assert!(torus().intervals(&ray).is_empty());
+
+        // Starts inside the tube and leaves it, crossing the other side
+        let ray = Ray::new(Vec3::new(-2.0, 0.0, 0.0), Vec3::X);
+        let intervals = torus().intervals(&ray);
+        assert_eq!(intervals.len(), 2);
+        assert!(intervals[0].enter.is_none());
+        let exit = intervals[0].exit.as_ref().unwrap();
+        assert!((exit.distance - 0.5).abs() < 1e-4 && exit.normal.abs_diff_eq(Vec3::X, 1e-3));
+    }
+
+    #[test]
+    fn grazing_ray_touches_the_top_of_the_tube() {
+        let hit = torus()
+            .hit(&Ray::new(Vec3::new(-5.0, 0.5, 0.0), Vec3::X))
+            .unwrap();
+
+        assert!(hit.point.abs_diff_eq(Vec3::new(-2.0, 0.5, 0.0), 1e-3));
+        assert!(hit.normal.abs_diff_eq(Vec3::Y, 1e-3));
+    }
+
+    #[test]
+    fn surface_samples_follow_the_area_density() {
+        fastrand::seed(3);
+        let torus = torus();
+        let count = 200_000;
+        let mut mean_cos = 0.0;
+        for _ in 0..count {
+            let (point, normal) = torus.sample_surface();
+            let distance = Vec2::new(point.x, point.z).length();
+            let cos_theta = (distance - 2.0) / 0.5;
+            assert!(((distance - 2.0).powi(2) + point.y * point.y - 0.25).abs() < 1e-4);
+            assert!((normal.length() - 1.0).abs() < 1e-4);
+            mean_cos += cos_theta / count as f32;
+        }
+
+        // Under a density proportional to R + r cos(theta), E[cos(theta)] = r / 2R
+        assert!((mean_cos - 0.125).abs() < 5e-3, "{}", mean_cos);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::geometry::{EmissionSample, Interval, Primitive};
+use crate::math::{Aabb, Intersection, Ray, Transform};
+use glam::Vec3;
+
+// Places a primitive defined in its own object space into the scene by
+// transforming rays into that space and hits back out
+pub struct Transformed<P> {
+    pub primitive: P,
+    pub transform: Transform,
+}
+
+impl<P: Primitive> Transformed<P> {
+    pub fn new(primitive: P, transform: Transform) -> Self {
+        Self {
+            primitive,
+            transform,
+        }
+    }
+}
+
+impl<P: Primitive> Primitive for Transformed<P> {
+    fn hit(&self, ray: &Ray) -> Option<Intersection> {
+        let (local_ray, distance_scale) = self.transform.inverse_ray(ray)?;
+        let hit = self.primitive.hit(&local_ray)?;
+
+        Some(self.transform.intersection_to_world(hit, distance_scale))
+    }
+
+    fn is_solid(&self) -> bool {
+        self.primitive.is_solid()
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((local_ray, distance_scale)) = self.transform.inverse_ray(ray) else {
+            return Vec::new();
+        };
+
+        self.primitive
+            .intervals(&local_ray)
+            .into_iter()
+            .map(|interval| {
+                interval.map(|hit| self.transform.intersection_to_world(hit, distance_scale))
+            })
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.primitive
+            .bounding_box()
+            .map(|bounds| self.transform.aabb(&bounds))
+    }
+
+    // Solid angle densities are unchanged by rotation, translation and
+    // uniform scale; other transforms are only found by BSDF sampling
+    fn is_emissive(&self) -> bool {
+        self.transform.uniform_scale.is_some() && self.primitive.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        let sample = self
+            .primitive
+            .sample_emission(self.transform.inverse_point(reference))?;
+
+        Some(EmissionSample {
+            point: self.transform.point(sample.point),
+            normal: self.transform.normal(sample.normal),
+            ..sample
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        let mut local_hit = hit.clone();
+        local_hit.point = self.transform.inverse_point(hit.point);
+        local_hit.normal = self.transform.inverse_normal(hit.normal);
+
+        self.primitive
+            .emission_pdf(self.transform.inverse_point(reference), &local_hit)
+    }
+}
@@ -1,7 +1,7 @@
-use crate::geometry::Primitive;
+use crate::geometry::{EmissionSample, Primitive};
 use crate::materials::Material;
-use crate::math::{Intersection, Ray};
-use glam::Vec3;
+use crate::math::{Aabb, Distribution1D, Intersection, Ray, sampling};
+use glam::{Vec2, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct Triangle {
@@ -9,6 +9,10 @@ pub struct Triangle {
     pub v1: Vec3,
     pub v2: Vec3,
     pub material: Material,
+    pub uvs: [Vec2; 3],
+    // Per-vertex normals and colors, interpolated across the face when set
+    pub normals: Option<[Vec3; 3]>,
+    pub colors: Option<[Vec3; 3]>,
     normal: Vec3,
 }
 
@@ -23,9 +27,60 @@ impl Triangle {
             v1,
             v2,
             material,
+            uvs: [Vec2::ZERO, Vec2::X, Vec2::Y],
+            normals: None,
+            colors: None,
             normal,
         }
     }
+
+    pub fn with_uvs(mut self, uvs: [Vec2; 3]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_colors(mut self, colors: [Vec3; 3]) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    pub fn area(&self) -> f32 {
+        (self.v1 - self.v0).cross(self.v2 - self.v0).length() * 0.5
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    // Directions of increasing u and v from the vertex UVs, falling back to
+    // an arbitrary frame when the UVs are degenerate
+    fn tangents(&self, edge1: Vec3, edge2: Vec3) -> (Vec3, Vec3) {
+        let duv1 = self.uvs[1] - self.uvs[0];
+        let duv2 = self.uvs[2] - self.uvs[0];
+        let determinant = duv1.x * duv2.y - duv1.y * duv2.x;
+
+        if determinant.abs() < 1e-12 {
+            return sampling::orthonormal_basis(self.normal);
+        }
+
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / determinant;
+
+        (
+            tangent.normalize_or(Vec3::X),
+            bitangent.normalize_or(Vec3::Y),
+        )
+    }
+
+    pub fn sample_point(&self) -> Vec3 {
+        let (b1, b2) = sampling::sample_triangle(fastrand::f32(), fastrand::f32());
+        self.v0 + (self.v1 - self.v0) * b1 + (self.v2 - self.v0) * b2
+    }
 }
 
 impl Primitive for Triangle {
@@ -60,14 +115,116 @@ impl Primitive for Triangle {
 
         if t > EPSILON {
             let point = ray.origin + t * ray.direction;
-            Some(Intersection::new(
-                t,
-                point,
-                self.normal,
-                self.material.clone(),
-            ))
+            let w = 1.0 - u - v;
+            let uv = self.uvs[0] * w + self.uvs[1] * u + self.uvs[2] * v;
+            let (tangent, bitangent) = self.tangents(edge1, edge2);
+
+            let mut hit = Intersection::new(t, point, self.normal, self.material.clone())
+                .with_uv(uv)
+                .with_tangents(tangent, bitangent);
+
+            if let Some([n0, n1, n2]) = self.normals
+                && let Some(shading_normal) = (n0 * w + n1 * u + n2 * v).try_normalize()
+            {
+                // Keep the geometric normal on the side the vertex normals
+                // face, in case the winding disagrees with them
+                if shading_normal.dot(self.normal) < 0.0 {
+                    hit.normal = -self.normal;
+                }
+                hit = hit.with_shading_normal(shading_normal);
+            }
+            if let Some([c0, c1, c2]) = self.colors {
+                hit = hit.with_color(c0 * w + c1 * u + c2 * v);
+            }
+
+            Some(hit)
         } else {
             None
         }
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(&[self.v0, self.v1, self.v2]))
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn sample_emission(&self, reference: Vec3) -> Option<EmissionSample> {
+        if !self.is_emissive() {
+            return None;
+        }
+
+        let point = self.sample_point();
+        let pdf = sampling::area_to_solid_angle(1.0 / self.area(), reference, point, self.normal);
+
+        (pdf > 0.0).then_some(EmissionSample {
+            point,
+            normal: self.normal,
+            radiance: self.material.emission,
+            pdf,
+        })
+    }
+
+    fn emission_pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if !self.is_emissive() {
+            return 0.0;
+        }
+
+        sampling::area_to_solid_angle(1.0 / self.area(), reference, hit.point, self.normal)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EmissiveTriangles {
+    indices: Vec<usize>,
+    distribution: Distribution1D,
+}
+
+impl EmissiveTriangles {
+    pub fn new(triangles: &[Triangle]) -> Self {
+        let indices: Vec<usize> = triangles
+            .iter()
+            .enumerate()
+            .filter(|(_, triangle)| triangle.is_emissive())
+            .map(|(index, _)| index)
+            .collect();
+
+        let areas: Vec<f32> = indices.iter().map(|&i| triangles[i].area()).collect();
+
+        Self {
+            indices,
+            distribution: Distribution1D::new(&areas),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distribution.is_empty()
+    }
+
+    pub fn sample(&self, triangles: &[Triangle], reference: Vec3) -> Option<EmissionSample> {
+        let (index, _) = self.distribution.sample(fastrand::f32())?;
+        let triangle = &triangles[self.indices[index]];
+
+        let point = triangle.sample_point();
+        let pdf_area = 1.0 / self.distribution.total();
+        let pdf = sampling::area_to_solid_angle(pdf_area, reference, point, triangle.normal);
+
+        (pdf > 0.0).then_some(EmissionSample {
+            point,
+            normal: triangle.normal,
+            radiance: triangle.material.emission,
+            pdf,
+        })
+    }
+
+    pub fn pdf(&self, reference: Vec3, hit: &Intersection) -> f32 {
+        if self.is_empty() || !hit.material.is_emissive() {
+            return 0.0;
+        }
+
+        let pdf_area = 1.0 / self.distribution.total();
+        sampling::area_to_solid_angle(pdf_area, reference, hit.point, hit.normal)
+    }
 }
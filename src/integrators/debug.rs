@@ -0,0 +1,68 @@
+use crate::integrators::Integrator;
+use crate::math::Ray;
+use crate::scene::Scene;
+use glam::Vec3;
+
+pub struct NormalIntegrator;
+
+impl Integrator for NormalIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        scene.hit(ray).map_or(Vec3::ZERO, |hit| {
+            hit.shading_normal * 0.5 + Vec3::splat(0.5)
+        })
+    }
+
+    fn is_radiance(&self) -> bool {
+        false
+    }
+}
+
+pub struct DepthIntegrator {
+    pub max_distance: f32,
+}
+
+impl DepthIntegrator {
+    pub fn new(max_distance: f32) -> Self {
+        Self {
+            max_distance: max_distance.max(f32::EPSILON),
+        }
+    }
+}
+
+impl Integrator for DepthIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        scene.hit(ray).map_or(Vec3::ZERO, |hit| {
+            Vec3::splat(1.0 - (hit.distance / self.max_distance).clamp(0.0, 1.0))
+        })
+    }
+
+    fn is_radiance(&self) -> bool {
+        false
+    }
+}
+
+pub struct AlbedoIntegrator;
+
+impl Integrator for AlbedoIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        scene.hit(ray).map_or(Vec3::ZERO, |hit| hit.material.albedo)
+    }
+
+    fn is_radiance(&self) -> bool {
+        false
+    }
+}
+
+pub struct UvIntegrator;
+
+impl Integrator for UvIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        scene.hit(ray).map_or(Vec3::ZERO, |hit| {
+            Vec3::new(hit.uv.x.rem_euclid(1.0), hit.uv.y.rem_euclid(1.0), 0.0)
+        })
+    }
+
+    fn is_radiance(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,41 @@
+pub mod debug;
+pub mod path;
+pub mod whitted;
+
+pub use debug::{AlbedoIntegrator, DepthIntegrator, NormalIntegrator, UvIntegrator};
+pub use path::PathIntegrator;
+pub use whitted::WhittedIntegrator;
+
+use crate::math::Ray;
+use crate::scene::Scene;
+use glam::Vec3;
+
+pub trait Integrator: Send + Sync {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3;
+
+    fn is_radiance(&self) -> bool {
+        true
+    }
+}
+
+pub fn reflect(incident: Vec3, normal: Vec3) -> Vec3 {
+    incident - 2.0 * incident.dot(normal) * normal
+}
+
+pub fn refract(incident: Vec3, normal: Vec3, eta: f32) -> Option<Vec3> {
+    let cos_i = (-incident).dot(normal).min(1.0);
+    let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+
+    if sin2_t > 1.0 {
+        return None;
+    }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some(eta * incident + (eta * cos_i - cos_t) * normal)
+}
+
+pub fn is_occluded(scene: &Scene, origin: Vec3, direction: Vec3, distance: f32) -> bool {
+    scene
+        .hit(&Ray::new(origin, direction))
+        .is_some_and(|hit| hit.distance < distance - 0.001)
+}
@@ -0,0 +1,259 @@
+use crate::integrators::{Integrator, is_occluded, reflect, refract};
+use crate::materials::Material;
+use crate::math::{Intersection, Ray, sampling};
+use crate::scene::Scene;
+use glam::Vec3;
+
+pub struct PathIntegrator {
+    pub max_depth: u32,
+}
+
+impl PathIntegrator {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+
+    fn trace_path(&self, scene: &Scene, camera_ray: &Ray) -> Vec3 {
+        let mut radiance = Vec3::ZERO;
+        let mut throughput = Vec3::ONE;
+        let mut ray = camera_ray.clone();
+        let mut specular_bounce = true;
+        let mut previous_point = ray.origin;
+        let mut previous_pdf = 0.0;
+
+        for bounce in 0..self.max_depth {
+            let Some(hit) = scene.hit(&ray) else {
+                let weight = match &scene.environment {
+                    Some(environment) if !specular_bounce => {
+                        let light_pdf = environment.pdf(ray.direction) / scene.light_count() as f32;
+                        sampling::power_heuristic(previous_pdf, light_pdf)
+                    }
+                    _ => 1.0,
+                };
+                radiance += throughput * scene.background(ray.direction) * weight;
+                break;
+            };
+
+            let material = &hit.material;
+            let view_dir = -ray.direction;
+
+            if material.is_emissive() {
+                let weight = if specular_bounce {
+                    1.0
+                } else {
+                    let light_pdf = scene.objects[hit.object_id].emission_pdf(previous_point, &hit)
+                        / scene.light_count() as f32;
+                    sampling::power_heuristic(previous_pdf, light_pdf)
+                };
+                radiance += throughput * material.emission * weight;
+            }
+
+            if material.is_transparent() && fastrand::f32() < material.transparency {
+                let (direction, weight, origin) = self.sample_dielectric(&hit, &ray);
+                throughput *= weight;
+                ray = Ray::new(origin, direction);
+                specular_bounce = true;
+            } else {
+                // The shading normal flips along with the geometric one so
+                // that both describe the side the path arrived from
+                let (normal, shading_normal) = if hit.normal.dot(view_dir) < 0.0 {
+                    (-hit.normal, -hit.shading_normal)
+                } else {
+                    (hit.normal, hit.shading_normal)
+                };
+
+                radiance += throughput
+                    * self.sample_direct_lighting(scene, &hit, normal, shading_normal, view_dir);
+
+                let Some(sample) = material.sample_brdf(shading_normal, view_dir) else {
+                    break;
+                };
+                // Perturbed normals can send samples through the actual surface
+                if normal.dot(sample.direction) <= 0.0 {
+                    break;
+                }
+
+                throughput *= sample.value * shading_normal.dot(sample.direction) / sample.pdf;
+                ray = Ray::new(hit.point + normal * 0.001, sample.direction);
+                specular_bounce = false;
+                previous_pdf = sample.pdf;
+            }
+
+            previous_point = hit.point;
+
+            if bounce >= 3 {
+                let survival = throughput.max_element().min(0.95);
+                if fastrand::f32() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+
+        radiance
+    }
+
+    fn sample_dielectric(&self, hit: &Intersection, ray: &Ray) -> (Vec3, Vec3, Vec3) {
+        let material = &hit.material;
+        let entering = ray.direction.dot(hit.normal) < 0.0;
+
+        let (normal, eta_i, eta_t) = if entering {
+            (hit.normal, 1.0, material.ior)
+        } else {
+            (-hit.normal, material.ior, 1.0)
+        };
+
+        let medium_transmittance = if entering {
+            Vec3::ONE
+        } else {
+            material.transmittance(hit.distance)
+        };
+
+        let cos_i = (-ray.direction).dot(normal);
+        let fresnel = Material::fresnel_dielectric(cos_i, eta_i, eta_t);
+
+        if fastrand::f32() >= fresnel
+            && let Some(refract_dir) = refract(ray.direction, normal, eta_i / eta_t)
+        {
+            let surface_tint = if entering && material.absorption <= 0.0 {
+                material.albedo
+            } else {
+                Vec3::ONE
+            };
+
+            return (
+                refract_dir.normalize(),
+                surface_tint * medium_transmittance,
+                hit.point - normal * 0.001,
+            );
+        }
+
+        (
+            reflect(ray.direction, normal).normalize(),
+            medium_transmittance,
+            hit.point + normal * 0.001,
+        )
+    }
+
+    fn sample_direct_lighting(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        normal: Vec3,
+        shading_normal: Vec3,
+        view_dir: Vec3,
+    ) -> Vec3 {
+        let light_count = scene.light_count();
+        if light_count == 0 {
+            return Vec3::ZERO;
+        }
+
+        let selection_pdf = 1.0 / light_count as f32;
+        let index = fastrand::usize(0..light_count);
+        let origin = hit.point + normal * 0.001;
+
+        if let Some(light) = scene.lights.get(index) {
+            let sample = light.sample(hit.point);
+            let light_dir = sample.direction;
+            let n_dot_l = shading_normal.dot(light_dir);
+
+            if n_dot_l <= 0.0
+                || normal.dot(light_dir) <= 0.0
+                || is_occluded(scene, origin, light_dir, sample.distance)
+            {
+                return Vec3::ZERO;
+            }
+
+            let brdf = hit
+                .material
+                .evaluate_brdf(shading_normal, view_dir, light_dir);
+            return brdf * sample.radiance * n_dot_l / selection_pdf;
+        }
+
+        let Some(emitter) = scene.emissive_object(index - scene.lights.len()) else {
+            return self.sample_environment(
+                scene,
+                hit,
+                normal,
+                shading_normal,
+                view_dir,
+                selection_pdf,
+            );
+        };
+        let Some(sample) = emitter.sample_emission(hit.point) else {
+            return Vec3::ZERO;
+        };
+
+        let to_light = sample.point - hit.point;
+        let distance = to_light.length();
+        if distance <= 0.001 || sample.pdf <= 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let light_dir = to_light / distance;
+        let n_dot_l = shading_normal.dot(light_dir);
+        if n_dot_l <= 0.0
+            || normal.dot(light_dir) <= 0.0
+            || is_occluded(scene, origin, light_dir, distance)
+        {
+            return Vec3::ZERO;
+        }
+
+        let light_pdf = sample.pdf * selection_pdf;
+        let brdf_pdf = hit.material.brdf_pdf(shading_normal, view_dir, light_dir);
+        let weight = sampling::power_heuristic(light_pdf, brdf_pdf);
+        let brdf = hit
+            .material
+            .evaluate_brdf(shading_normal, view_dir, light_dir);
+
+        brdf * sample.radiance * n_dot_l * weight / light_pdf
+    }
+
+    fn sample_environment(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        normal: Vec3,
+        shading_normal: Vec3,
+        view_dir: Vec3,
+        selection_pdf: f32,
+    ) -> Vec3 {
+        let Some(environment) = &scene.environment else {
+            return Vec3::ZERO;
+        };
+        let Some(sample) = environment.sample(fastrand::f32(), fastrand::f32()) else {
+            return Vec3::ZERO;
+        };
+
+        let n_dot_l = shading_normal.dot(sample.direction);
+        if n_dot_l <= 0.0
+            || normal.dot(sample.direction) <= 0.0
+            || sample.pdf <= 0.0
+            || is_occluded(
+                scene,
+                hit.point + normal * 0.001,
+                sample.direction,
+                f32::INFINITY,
+            )
+        {
+            return Vec3::ZERO;
+        }
+
+        let light_pdf = sample.pdf * selection_pdf;
+        let brdf_pdf = hit
+            .material
+            .brdf_pdf(shading_normal, view_dir, sample.direction);
+        let weight = sampling::power_heuristic(light_pdf, brdf_pdf);
+        let brdf = hit
+            .material
+            .evaluate_brdf(shading_normal, view_dir, sample.direction);
+
+        brdf * sample.radiance * n_dot_l * weight / light_pdf
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        self.trace_path(scene, ray)
+    }
+}
@@ -0,0 +1,325 @@
+use crate::integrators::{Integrator, reflect, refract};
+use crate::lighting::Light;
+use crate::materials::Material;
+use crate::math::{Intersection, Ray};
+use crate::scene::Scene;
+use glam::Vec3;
+
+pub struct WhittedIntegrator {
+    pub max_depth: u32,
+}
+
+impl WhittedIntegrator {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+
+    fn trace_ray(&self, scene: &Scene, ray: &Ray, depth: u32) -> Vec3 {
+        if depth >= self.max_depth {
+            return Vec3::ZERO;
+        }
+
+        if let Some(hit) = scene.hit(ray) {
+            let view_dir = -ray.direction;
+            let material = &hit.material;
+
+            let opaque_color = if material.transparency < 1.0 {
+                self.calculate_opaque_color(scene, &hit, view_dir, depth)
+            } else {
+                Vec3::ZERO
+            };
+
+            if material.is_transparent() {
+                let transmitted_color = self.calculate_refraction(scene, &hit, ray, depth);
+                return opaque_color * (1.0 - material.transparency)
+                    + transmitted_color * material.transparency;
+            }
+            opaque_color
+        } else {
+            scene.background(ray.direction)
+        }
+    }
+
+    fn calculate_opaque_color(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        view_dir: Vec3,
+        depth: u32,
+    ) -> Vec3 {
+        let material = &hit.material;
+        let direct_lighting = self.calculate_pbr_lighting(scene, hit, view_dir);
+
+        if material.metallic > 0.0 || (material.roughness < 0.3 && !material.albedo.is_nan()) {
+            let reflected_color = self.calculate_reflection(scene, hit, view_dir, depth);
+            let reflection_strength = material.metallic.max(1.0 - material.roughness);
+
+            return direct_lighting * (1.0 - reflection_strength * 0.5)
+                + reflected_color * reflection_strength * 0.5;
+        }
+        direct_lighting
+    }
+
+    fn calculate_refraction(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        ray: &Ray,
+        depth: u32,
+    ) -> Vec3 {
+        let material = &hit.material;
+        let entering = ray.direction.dot(hit.normal) < 0.0;
+
+        let (normal, eta_i, eta_t) = if entering {
+            (hit.normal, 1.0, material.ior)
+        } else {
+            (-hit.normal, material.ior, 1.0)
+        };
+
+        let cos_i = (-ray.direction).dot(normal);
+        let fresnel = Material::fresnel_dielectric(cos_i, eta_i, eta_t);
+
+        // Ray segments that end on an exit hit travelled through the medium.
+        let medium_transmittance = if entering {
+            Vec3::ONE
+        } else {
+            material.transmittance(hit.distance)
+        };
+
+        let reflect_dir = reflect(ray.direction, normal).normalize();
+        let reflect_ray = Ray::new(hit.point + normal * 0.001, reflect_dir);
+
+        let Some(refract_dir) = refract(ray.direction, normal, eta_i / eta_t) else {
+            return self.trace_ray(scene, &reflect_ray, depth + 1) * medium_transmittance;
+        };
+
+        let refract_ray = Ray::new(hit.point - normal * 0.001, refract_dir.normalize());
+
+        // Thin glass without absorption is tinted once at the entry surface.
+        let surface_tint = if entering && material.absorption <= 0.0 {
+            material.albedo
+        } else {
+            Vec3::ONE
+        };
+
+        // Following both branches doubles the ray count per bounce, so deeper
+        // bounces pick one branch with probability equal to its Fresnel weight.
+        let color = if depth < 2 {
+            let reflected_color = self.trace_ray(scene, &reflect_ray, depth + 1);
+            let refracted_color = self.trace_ray(scene, &refract_ray, depth + 1);
+            reflected_color * fresnel + refracted_color * surface_tint * (1.0 - fresnel)
+        } else if fastrand::f32() < fresnel {
+            self.trace_ray(scene, &reflect_ray, depth + 1)
+        } else {
+            self.trace_ray(scene, &refract_ray, depth + 1) * surface_tint
+        };
+
+        color * medium_transmittance
+    }
+
+    fn calculate_reflection(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        view_dir: Vec3,
+        depth: u32,
+    ) -> Vec3 {
+        let reflect_dir = reflect(-view_dir, hit.shading_normal).normalize();
+        let reflect_ray = Ray::new(hit.point + hit.normal * 0.001, reflect_dir);
+
+        let reflected_color = self.trace_ray(scene, &reflect_ray, depth + 1);
+
+        if hit.material.metallic > 0.5 {
+            reflected_color * hit.material.albedo
+        } else {
+            reflected_color
+        }
+    }
+
+    fn calculate_pbr_lighting(&self, scene: &Scene, hit: &Intersection, view_dir: Vec3) -> Vec3 {
+        let material = &hit.material;
+        let mut lo = Vec3::ZERO;
+
+        let ambient = Vec3::splat(0.03) * material.albedo * material.ao;
+
+        for light in &scene.lights {
+            let shadow_factor =
+                self.calculate_shadow_factor(scene, hit.point, hit.normal, light.as_ref());
+
+            if shadow_factor > 0.0 {
+                let sample = light.illuminate(hit.point);
+                let light_dir = sample.direction;
+                let radiance = sample.radiance;
+
+                let n_dot_l = hit.shading_normal.dot(light_dir).max(0.0);
+                let brdf = material.evaluate_brdf(hit.shading_normal, view_dir, light_dir);
+
+                lo += brdf * radiance * n_dot_l * shadow_factor;
+            }
+        }
+
+        lo += self.calculate_emissive_lighting(scene, hit, view_dir);
+        lo += self.calculate_environment_lighting(scene, hit, view_dir);
+
+        ambient + lo + material.emission
+    }
+
+    fn calculate_emissive_lighting(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        view_dir: Vec3,
+    ) -> Vec3 {
+        const EMISSIVE_SAMPLES: u32 = 4;
+
+        let mut lo = Vec3::ZERO;
+        let shadow_ray_origin = hit.point + hit.normal * 0.001;
+
+        for emitter in scene.emissive_objects() {
+            let mut emitter_lo = Vec3::ZERO;
+
+            for _ in 0..EMISSIVE_SAMPLES {
+                let Some(sample) = emitter.sample_emission(hit.point) else {
+                    continue;
+                };
+
+                let to_light = sample.point - hit.point;
+                let distance = to_light.length();
+                if distance <= 0.001 || sample.pdf <= 0.0 {
+                    continue;
+                }
+
+                let light_dir = to_light / distance;
+                let n_dot_l = hit.shading_normal.dot(light_dir);
+                if n_dot_l <= 0.0 {
+                    continue;
+                }
+
+                let visibility =
+                    self.calculate_transmittance(scene, shadow_ray_origin, light_dir, distance);
+                if visibility <= 0.0 {
+                    continue;
+                }
+
+                let brdf = hit
+                    .material
+                    .evaluate_brdf(hit.shading_normal, view_dir, light_dir);
+                emitter_lo += brdf * sample.radiance * n_dot_l * visibility / sample.pdf;
+            }
+
+            lo += emitter_lo / EMISSIVE_SAMPLES as f32;
+        }
+
+        lo
+    }
+
+    fn calculate_environment_lighting(
+        &self,
+        scene: &Scene,
+        hit: &Intersection,
+        view_dir: Vec3,
+    ) -> Vec3 {
+        const ENVIRONMENT_SAMPLES: u32 = 8;
+
+        let Some(environment) = &scene.environment else {
+            return Vec3::ZERO;
+        };
+
+        let mut lo = Vec3::ZERO;
+        let shadow_ray_origin = hit.point + hit.normal * 0.001;
+
+        for _ in 0..ENVIRONMENT_SAMPLES {
+            let Some(sample) = environment.sample(fastrand::f32(), fastrand::f32()) else {
+                continue;
+            };
+
+            let n_dot_l = hit.shading_normal.dot(sample.direction);
+            if n_dot_l <= 0.0 || sample.pdf <= 0.0 {
+                continue;
+            }
+
+            let visibility = self.calculate_transmittance(
+                scene,
+                shadow_ray_origin,
+                sample.direction,
+                f32::INFINITY,
+            );
+            if visibility <= 0.0 {
+                continue;
+            }
+
+            let brdf = hit
+                .material
+                .evaluate_brdf(hit.shading_normal, view_dir, sample.direction);
+            lo += brdf * sample.radiance * n_dot_l * visibility / sample.pdf;
+        }
+
+        lo / ENVIRONMENT_SAMPLES as f32
+    }
+
+    fn calculate_shadow_factor(
+        &self,
+        scene: &Scene,
+        point: Vec3,
+        normal: Vec3,
+        light: &dyn Light,
+    ) -> f32 {
+        let samples = if light.is_delta() { 1 } else { 8 };
+
+        let mut shadow_factor = 0.0;
+        let shadow_ray_origin = point + normal * 0.001;
+
+        for _ in 0..samples {
+            let sample = light.sample(point);
+
+            shadow_factor += self.calculate_transmittance(
+                scene,
+                shadow_ray_origin,
+                sample.direction,
+                sample.distance,
+            );
+        }
+
+        shadow_factor / samples as f32
+    }
+
+    fn calculate_transmittance(
+        &self,
+        scene: &Scene,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> f32 {
+        let mut transmittance = 1.0;
+        let mut origin = origin;
+        let mut remaining = max_distance;
+
+        for _ in 0..self.max_depth {
+            let shadow_ray = Ray::new(origin, direction);
+
+            let Some(hit) = scene.hit(&shadow_ray) else {
+                break;
+            };
+
+            if hit.distance >= remaining - 0.001 {
+                break;
+            }
+
+            transmittance *= hit.material.transparency;
+            if transmittance <= 0.0 {
+                return 0.0;
+            }
+
+            origin = hit.point + direction * 0.001;
+            remaining -= hit.distance + 0.001;
+        }
+
+        transmittance
+    }
+}
+
+impl Integrator for WhittedIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray) -> Vec3 {
+        self.trace_ray(scene, ray, 0)
+    }
+}
@@ -1,17 +1,24 @@
 pub mod config;
 pub mod error;
 pub mod geometry;
+pub mod integrators;
 pub mod lighting;
+pub mod loaders;
 pub mod materials;
 pub mod math;
 pub mod renderer;
 pub mod scene;
+pub mod textures;
 
 pub use config::*;
 pub use error::{RaystError, Result};
-pub use geometry::{Cube, Plane, Pyramid, Sphere};
-pub use lighting::PointLight;
-pub use materials::Material;
+pub use geometry::{
+    Capsule, Cone, Csg, CsgOperation, Cube, Cylinder, Disk, Group, Mesh, Plane, Pyramid, Rectangle,
+    Sphere, Torus, Transformed,
+};
+pub use integrators::{Integrator, PathIntegrator, WhittedIntegrator};
+pub use lighting::{DirectionalLight, Light, PointLight, SpotLight};
+pub use materials::{Material, MaterialLibrary};
 pub use math::{Intersection, Ray};
-pub use renderer::Raytracer;
-pub use scene::{Camera, Scene};
+pub use renderer::{OutputFormat, Raytracer, save_aovs, save_image, save_multilayer_exr};
+pub use scene::{Camera, Projection, Scene};
@@ -0,0 +1,53 @@
+use crate::lighting::{Light, LightSample};
+use crate::math::sampling;
+use glam::Vec3;
+
+// Light from an infinitely distant source such as the sun. `direction` is the
+// direction the light travels in; `angular_diameter` (degrees) softens shadows.
+#[derive(Debug, Clone)]
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub angular_diameter: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, color: Vec3, intensity: f32, angular_diameter: f32) -> Self {
+        Self {
+            direction: direction.normalize(),
+            color,
+            intensity,
+            angular_diameter,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn illuminate(&self, _point: Vec3) -> LightSample {
+        LightSample {
+            direction: -self.direction,
+            distance: f32::INFINITY,
+            radiance: self.color * self.intensity,
+        }
+    }
+
+    fn sample(&self, point: Vec3) -> LightSample {
+        if self.angular_diameter <= 0.0 {
+            return self.illuminate(point);
+        }
+
+        let cos_theta_max = (self.angular_diameter.to_radians() / 2.0).cos();
+        let local = sampling::sample_cone(fastrand::f32(), fastrand::f32(), cos_theta_max);
+
+        LightSample {
+            direction: sampling::to_world(local, -self.direction),
+            distance: f32::INFINITY,
+            radiance: self.color * self.intensity,
+        }
+    }
+
+    fn is_delta(&self) -> bool {
+        self.angular_diameter <= 0.0
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::{RaystError, Result};
+use crate::math::Distribution1D;
+use glam::{Mat3, Vec3};
+use std::f32::consts::PI;
+use std::path::Path;
+
+pub struct EnvironmentSample {
+    pub direction: Vec3,
+    pub radiance: Vec3,
+    pub pdf: f32,
+}
+
+// Equirectangular environment map surrounding the scene. The image center
+// looks down -Z with +Y at the top; `rotation` turns the map around +Y.
+pub struct EnvironmentMap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vec3>,
+    pub intensity: f32,
+    rotation: Mat3,
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl EnvironmentMap {
+    pub fn load_from_file<P: AsRef<Path>>(path: P, rotation: f32, intensity: f32) -> Result<Self> {
+        let image = image::open(path.as_ref())?.into_rgb32f();
+        let (width, height) = image.dimensions();
+        let pixels = image.pixels().map(|p| Vec3::from_array(p.0)).collect();
+
+        Self::new(pixels, width as usize, height as usize, rotation, intensity)
+    }
+
+    pub fn new(
+        pixels: Vec<Vec3>,
+        width: usize,
+        height: usize,
+        rotation: f32,
+        intensity: f32,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 || pixels.len() != width * height {
+            return Err(RaystError::InvalidInput(format!(
+                "Environment map needs {}x{} pixels, got {}",
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+
+        // Rows near the poles cover less solid angle, weight them by sin(theta)
+        let rows: Vec<Distribution1D> = pixels
+            .chunks(width)
+            .enumerate()
+            .map(|(row, pixels)| {
+                let sin_theta = ((row as f32 + 0.5) / height as f32 * PI).sin();
+                let weights: Vec<f32> = pixels
+                    .iter()
+                    .map(|p| luminance(*p).max(0.0) * sin_theta)
+                    .collect();
+                Distribution1D::new(&weights)
+            })
+            .collect();
+        let marginal = Distribution1D::new(&rows.iter().map(|row| row.total()).collect::<Vec<_>>());
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            intensity,
+            rotation: Mat3::from_rotation_y(rotation.to_radians()),
+            rows,
+            marginal,
+        })
+    }
+
+    pub fn radiance(&self, direction: Vec3) -> Vec3 {
+        let (column, row, _) = self.pixel_of(direction);
+        self.pixels[row * self.width + column] * self.intensity
+    }
+
+    pub fn sample(&self, u1: f32, u2: f32) -> Option<EnvironmentSample> {
+        let (row, row_pdf) = self.marginal.sample(u1)?;
+        let (column, column_pdf) = self.rows[row].sample(u2)?;
+
+        // Jitter inside the chosen pixel, the density is constant over it
+        let u = (column as f32 + fastrand::f32()) / self.width as f32;
+        let v = (row as f32 + fastrand::f32()) / self.height as f32;
+
+        let phi = (u - 0.5) * 2.0 * PI;
+        let theta = v * PI;
+        let sin_theta = theta.sin();
+        if sin_theta <= 1e-6 {
+            return None;
+        }
+
+        let local = Vec3::new(sin_theta * phi.sin(), theta.cos(), -sin_theta * phi.cos());
+        let pdf =
+            row_pdf * column_pdf * (self.width * self.height) as f32 / (2.0 * PI * PI * sin_theta);
+
+        Some(EnvironmentSample {
+            direction: self.rotation * local,
+            radiance: self.pixels[row * self.width + column] * self.intensity,
+            pdf,
+        })
+    }
+
+    pub fn pdf(&self, direction: Vec3) -> f32 {
+        let (column, row, sin_theta) = self.pixel_of(direction);
+        if sin_theta <= 1e-6 {
+            return 0.0;
+        }
+
+        self.marginal.pdf(row) * self.rows[row].pdf(column) * (self.width * self.height) as f32
+            / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn pixel_of(&self, direction: Vec3) -> (usize, usize, f32) {
+        let local = self.rotation.transpose() * direction.normalize();
+        let theta = local.y.clamp(-1.0, 1.0).acos();
+        let phi = local.x.atan2(-local.z);
+
+        let u = phi / (2.0 * PI) + 0.5;
+        let v = theta / PI;
+
+        let column = ((u * self.width as f32) as usize).min(self.width - 1);
+        let row = ((v * self.height as f32) as usize).min(self.height - 1);
+
+        (column, row, theta.sin())
+    }
+}
+
+fn luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
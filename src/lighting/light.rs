@@ -0,0 +1,23 @@
+use glam::Vec3;
+
+pub struct LightSample {
+    pub direction: Vec3,
+    pub distance: f32,
+    pub radiance: Vec3,
+}
+
+pub trait Light: Send + Sync {
+    // Light arriving at `point` from the center of the light
+    fn illuminate(&self, point: Vec3) -> LightSample;
+
+    // Light arriving at `point` from a random point on the light's extent,
+    // averaged over many calls to get soft shadows
+    fn sample(&self, point: Vec3) -> LightSample {
+        self.illuminate(point)
+    }
+
+    // Delta lights have no extent, so a single shadow ray is exact
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
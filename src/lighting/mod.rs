@@ -1,3 +1,13 @@
+pub mod directional_light;
+pub mod environment;
+pub mod light;
 pub mod point_light;
+pub mod sky;
+pub mod spot_light;
 
+pub use directional_light::DirectionalLight;
+pub use environment::{EnvironmentMap, EnvironmentSample};
+pub use light::{Light, LightSample};
 pub use point_light::PointLight;
+pub use sky::SkyModel;
+pub use spot_light::SpotLight;
@@ -1,3 +1,5 @@
+use crate::lighting::{Light, LightSample};
+use crate::loaders::IesProfile;
 use glam::Vec3;
 
 #[derive(Debug, Clone)]
@@ -5,6 +7,8 @@ pub struct PointLight {
     pub position: Vec3,
     pub color: Vec3,
     pub intensity: f32,
+    // Photometric profile with its nadir pointing down (-Y)
+    pub profile: Option<IesProfile>,
 }
 
 impl PointLight {
@@ -13,6 +17,7 @@ impl PointLight {
             position,
             color,
             intensity,
+            profile: None,
         }
     }
 
@@ -25,3 +30,22 @@ impl PointLight {
         self.intensity / (1.0 + distance * distance * 0.01)
     }
 }
+
+impl Light for PointLight {
+    fn illuminate(&self, point: Vec3) -> LightSample {
+        let to_light = self.position - point;
+        let distance = to_light.length();
+        let direction = to_light / distance;
+
+        let scale = self
+            .profile
+            .as_ref()
+            .map_or(1.0, |profile| profile.evaluate(Vec3::NEG_Y, -direction));
+
+        LightSample {
+            direction,
+            distance,
+            radiance: self.color * self.intensity * scale / (distance * distance),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::error::{RaystError, Result};
+use crate::lighting::{DirectionalLight, EnvironmentMap};
+use glam::Vec3;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+// Converts the model's luminance (kcd/m^2) to scene radiance, and the clear
+// sky sun irradiance at the top of the atmosphere in the same units
+const SKY_SCALE: f32 = 0.05;
+const SUN_IRRADIANCE: f32 = 12.0;
+const SUN_ANGULAR_DIAMETER: f32 = 0.53;
+
+// Preetham et al. 1999 analytic daylight model. Azimuth 0 puts the sun
+// towards -Z, increasing towards +X.
+#[derive(Debug, Clone)]
+pub struct SkyModel {
+    pub sun_direction: Vec3,
+    pub turbidity: f32,
+    zenith: Vec3,
+    perez: [[f32; 5]; 3],
+}
+
+impl SkyModel {
+    pub fn new(sun_elevation: f32, sun_azimuth: f32, turbidity: f32) -> Result<Self> {
+        if !(0.0..=90.0).contains(&sun_elevation) {
+            return Err(RaystError::InvalidInput(format!(
+                "Sky sun_elevation must be between 0 and 90 degrees, got {}",
+                sun_elevation
+            )));
+        }
+        if !(1.7..=10.0).contains(&turbidity) {
+            return Err(RaystError::InvalidInput(format!(
+                "Sky turbidity must be between 1.7 and 10, got {}",
+                turbidity
+            )));
+        }
+
+        let elevation = sun_elevation.to_radians();
+        let azimuth = sun_azimuth.to_radians();
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        let t = turbidity;
+        let theta_s = FRAC_PI_2 - elevation;
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = zenith_chromaticity(
+            t,
+            theta_s,
+            [
+                [0.00166, -0.00375, 0.00209, 0.0],
+                [-0.02903, 0.06377, -0.03202, 0.00394],
+                [0.11693, -0.21196, 0.06052, 0.25886],
+            ],
+        );
+        let zenith_y = zenith_chromaticity(
+            t,
+            theta_s,
+            [
+                [0.00275, -0.00610, 0.00317, 0.0],
+                [-0.04214, 0.08970, -0.04153, 0.00516],
+                [0.15346, -0.26756, 0.06670, 0.26688],
+            ],
+        );
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // Normalize so that the zenith gets exactly the zenith values
+        let mut zenith = Vec3::new(zenith_luminance, zenith_x, zenith_y);
+        for (channel, coefficients) in perez.iter().enumerate() {
+            zenith[channel] /= perez_function(coefficients, 0.0, theta_s);
+        }
+
+        Ok(Self {
+            sun_direction,
+            turbidity,
+            zenith,
+            perez,
+        })
+    }
+
+    pub fn radiance(&self, direction: Vec3) -> Vec3 {
+        let direction = direction.normalize();
+
+        // Below the horizon, show a darkened reflection of the horizon
+        let (direction, ground) = if direction.y < 0.01 {
+            let horizontal = Vec3::new(direction.x, 0.0, direction.z).normalize_or(Vec3::NEG_Z);
+            ((horizontal + Vec3::Y * 0.01).normalize(), direction.y < 0.0)
+        } else {
+            (direction, false)
+        };
+
+        let theta = direction.y.clamp(-1.0, 1.0).acos();
+        let gamma = direction.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let [luminance, x, y] = std::array::from_fn(|channel| {
+            self.zenith[channel] * perez_function(&self.perez[channel], theta, gamma)
+        });
+
+        let color = xyy_to_linear_srgb(x, y, luminance) * SKY_SCALE;
+        if ground { color * 0.3 } else { color }
+    }
+
+    // Attenuation of direct sunlight through the atmosphere, using Rayleigh
+    // and Angstrom aerosol optical depths at red, green and blue wavelengths
+    pub fn sun_transmittance(&self) -> Vec3 {
+        let zenith_angle = self.sun_direction.y.clamp(0.0, 1.0).acos().to_degrees();
+        let air_mass = 1.0
+            / (zenith_angle.to_radians().cos() + 0.50572 * (96.07995 - zenith_angle).powf(-1.6364));
+
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let wavelengths = Vec3::new(0.65, 0.55, 0.45);
+        let optical_depth = Vec3::new(
+            optical_depth(wavelengths.x, beta),
+            optical_depth(wavelengths.y, beta),
+            optical_depth(wavelengths.z, beta),
+        );
+
+        (-optical_depth * air_mass).exp()
+    }
+
+    pub fn sun_light(&self, intensity: f32) -> DirectionalLight {
+        DirectionalLight::new(
+            -self.sun_direction,
+            self.sun_transmittance(),
+            SUN_IRRADIANCE * intensity,
+            SUN_ANGULAR_DIAMETER,
+        )
+    }
+
+    pub fn to_environment(
+        &self,
+        width: usize,
+        height: usize,
+        intensity: f32,
+    ) -> Result<EnvironmentMap> {
+        let pixels = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .map(|(row, column)| {
+                let phi = ((column as f32 + 0.5) / width as f32 - 0.5) * 2.0 * PI;
+                let theta = (row as f32 + 0.5) / height as f32 * PI;
+                let direction = Vec3::new(
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                    -theta.sin() * phi.cos(),
+                );
+                self.radiance(direction)
+            })
+            .collect();
+
+        EnvironmentMap::new(pixels, width, height, 0.0, intensity)
+    }
+}
+
+fn perez_function(coefficients: &[f32; 5], theta: f32, gamma: f32) -> f32 {
+    let [a, b, c, d, e] = *coefficients;
+    let cos_gamma = gamma.cos();
+
+    (1.0 + a * (b / theta.cos().max(0.01)).exp())
+        * (1.0 + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+}
+
+fn zenith_chromaticity(turbidity: f32, theta_s: f32, matrix: [[f32; 4]; 3]) -> f32 {
+    let t = [turbidity * turbidity, turbidity, 1.0];
+    let theta = [theta_s.powi(3), theta_s * theta_s, theta_s, 1.0];
+
+    (0..3)
+        .map(|i| t[i] * (0..4).map(|j| matrix[i][j] * theta[j]).sum::<f32>())
+        .sum()
+}
+
+fn optical_depth(wavelength: f32, beta: f32) -> f32 {
+    let rayleigh = 0.008735 * wavelength.powf(-4.08);
+    let aerosol = beta * wavelength.powf(-1.3);
+    rayleigh + aerosol
+}
+
+fn xyy_to_linear_srgb(x: f32, y: f32, luminance: f32) -> Vec3 {
+    if y <= 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Vec3::new(
+        3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z,
+    )
+    .max(Vec3::ZERO)
+}
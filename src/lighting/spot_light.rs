@@ -0,0 +1,69 @@
+use crate::lighting::{Light, LightSample};
+use crate::loaders::IesProfile;
+use glam::Vec3;
+
+// Cone angles are measured from the spot axis, in degrees. Intensity fades
+// smoothly from full at `inner_angle` to zero at `outer_angle`.
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub profile: Option<IesProfile>,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            inner_angle,
+            outer_angle,
+            profile: None,
+        }
+    }
+
+    pub fn falloff(&self, emitted: Vec3) -> f32 {
+        let cos_outer = self.outer_angle.to_radians().cos();
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_theta = emitted.dot(self.direction);
+
+        if cos_inner - cos_outer <= 1e-6 {
+            return if cos_theta >= cos_outer { 1.0 } else { 0.0 };
+        }
+
+        let t = ((cos_theta - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn illuminate(&self, point: Vec3) -> LightSample {
+        let to_light = self.position - point;
+        let distance = to_light.length();
+        let direction = to_light / distance;
+
+        let mut scale = self.falloff(-direction);
+        if let Some(profile) = &self.profile {
+            scale *= profile.evaluate(self.direction, -direction);
+        }
+
+        LightSample {
+            direction,
+            distance,
+            radiance: self.color * self.intensity * scale / (distance * distance),
+        }
+    }
+}
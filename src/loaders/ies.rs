@@ -0,0 +1,237 @@
+use crate::error::{RaystError, Result};
+use glam::Vec3;
+use std::path::Path;
+
+// Type C photometric data from an IES LM-63 file. Vertical angles are measured
+// from the nadir of the fixture, horizontal angles around its axis.
+#[derive(Debug, Clone)]
+pub struct IesProfile {
+    pub vertical_angles: Vec<f32>,
+    pub horizontal_angles: Vec<f32>,
+    // One row of vertical samples per horizontal angle, in candela
+    pub candela: Vec<Vec<f32>>,
+    pub max_candela: f32,
+    // Turns the profile about its nadir, in degrees counterclockwise seen
+    // from above the fixture
+    pub rotation: f32,
+}
+
+impl IesProfile {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        // Keyword lines are often Latin-1 rather than UTF-8
+        let content = String::from_utf8_lossy(&std::fs::read(path)?).into_owned();
+        Self::parse(&content)
+            .map_err(|msg| RaystError::InvalidInput(format!("{}: {}", path.display(), msg)))
+    }
+
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let mut lines = content.lines();
+        let tilt = lines
+            .by_ref()
+            .map(str::trim)
+            .find(|line| line.starts_with("TILT"))
+            .ok_or("missing TILT line")?;
+
+        let mut numbers =
+            lines.flat_map(|line| line.split([' ', '\t', ',']).filter(|t| !t.is_empty()));
+        let mut next = |what: &str| -> std::result::Result<f32, String> {
+            let token = numbers
+                .next()
+                .ok_or_else(|| format!("unexpected end of file, expected {}", what))?;
+            token
+                .parse::<f32>()
+                .map_err(|_| format!("invalid number '{}' for {}", token, what))
+        };
+
+        if tilt
+            .trim_start_matches("TILT")
+            .trim_start_matches([' ', '='])
+            == "INCLUDE"
+        {
+            next("lamp to luminaire geometry")?;
+            let pairs = next("tilt angle count")? as usize;
+            for _ in 0..pairs * 2 {
+                next("tilt data")?;
+            }
+        }
+
+        next("number of lamps")?;
+        next("lumens per lamp")?;
+        let multiplier = next("candela multiplier")?;
+        let vertical_count = next("vertical angle count")? as usize;
+        let horizontal_count = next("horizontal angle count")? as usize;
+        let photometric_type = next("photometric type")? as u32;
+        for what in [
+            "units type",
+            "width",
+            "length",
+            "height",
+            "ballast factor",
+            "future use",
+            "input watts",
+        ] {
+            next(what)?;
+        }
+
+        if photometric_type != 1 {
+            return Err(format!(
+                "unsupported photometric type {}, only type C is supported",
+                photometric_type
+            ));
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err(String::from("profile has no angles"));
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next("vertical angle"))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next("horizontal angle"))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut candela = Vec::with_capacity(horizontal_count);
+        for _ in 0..horizontal_count {
+            let row = (0..vertical_count)
+                .map(|_| next("candela value").map(|value| value * multiplier))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            candela.push(row);
+        }
+
+        let sorted = |angles: &[f32]| angles.windows(2).all(|pair| pair[0] < pair[1]);
+        if !sorted(&vertical_angles) || !sorted(&horizontal_angles) {
+            return Err(String::from("angles must be in increasing order"));
+        }
+
+        let max_candela = candela
+            .iter()
+            .flatten()
+            .fold(0.0_f32, |max, &value| max.max(value));
+        if max_candela <= 0.0 {
+            return Err(String::from("profile has no positive candela values"));
+        }
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            max_candela,
+            rotation: 0.0,
+        })
+    }
+
+    // Relative intensity in [0, 1] towards `direction` for a fixture whose
+    // nadir points along `axis`
+    pub fn evaluate(&self, axis: Vec3, direction: Vec3) -> f32 {
+        let (c0, c90) = Self::horizontal_frame(axis);
+        let vertical = direction.dot(axis).clamp(-1.0, 1.0).acos().to_degrees();
+        let horizontal = direction.dot(c90).atan2(direction.dot(c0)).to_degrees() - self.rotation;
+
+        self.candela_at(vertical, horizontal.rem_euclid(360.0)) / self.max_candela
+    }
+
+    // C0 is world +X projected across the axis, so it doesn't move as a
+    // fixture tilts. Fixtures aimed along X use +Z instead. A downlight ends
+    // up with C0 on +X and C90 on -Z.
+    fn horizontal_frame(axis: Vec3) -> (Vec3, Vec3) {
+        let reference = if axis.x.abs() < 0.999 {
+            Vec3::X
+        } else {
+            Vec3::Z
+        };
+        let c0 = (reference - axis * reference.dot(axis)).normalize();
+        (c0, c0.cross(axis))
+    }
+
+    fn candela_at(&self, vertical: f32, horizontal: f32) -> f32 {
+        // Fold the horizontal angle into the range covered by the file's symmetry
+        let last = *self.horizontal_angles.last().unwrap_or(&0.0);
+        let horizontal = if last <= 0.0 {
+            0.0
+        } else if last <= 90.0 {
+            let h = if horizontal > 180.0 {
+                360.0 - horizontal
+            } else {
+                horizontal
+            };
+            if h > 90.0 { 180.0 - h } else { h }
+        } else if last <= 180.0 && horizontal > 180.0 {
+            360.0 - horizontal
+        } else {
+            horizontal
+        };
+
+        let Some((v0, v1, tv)) = bracket(&self.vertical_angles, vertical) else {
+            return 0.0;
+        };
+        let (h0, h1, th) = bracket(&self.horizontal_angles, horizontal)
+            .or_else(|| wrap_bracket(&self.horizontal_angles, horizontal))
+            .unwrap_or((0, 0, 0.0));
+
+        let lerp = |row: &[f32]| row[v0] + (row[v1] - row[v0]) * tv;
+        let a = lerp(&self.candela[h0]);
+        let b = lerp(&self.candela[h1]);
+
+        a + (b - a) * th
+    }
+}
+
+// Indices and blend factor of the samples around `x`, None when out of range
+fn bracket(angles: &[f32], x: f32) -> Option<(usize, usize, f32)> {
+    let first = *angles.first()?;
+    let last = *angles.last()?;
+    if x < first - 1e-3 || x > last + 1e-3 {
+        return None;
+    }
+    if angles.len() == 1 {
+        return Some((0, 0, 0.0));
+    }
+
+    let upper = angles
+        .partition_point(|&angle| angle < x)
+        .clamp(1, angles.len() - 1);
+    let lower = upper - 1;
+    let t = ((x - angles[lower]) / (angles[upper] - angles[lower])).clamp(0.0, 1.0);
+
+    Some((lower, upper, t))
+}
+
+// Full-circle profiles that stop short of 360 degrees wrap back to the first angle
+fn wrap_bracket(angles: &[f32], x: f32) -> Option<(usize, usize, f32)> {
+    let first = *angles.first()?;
+    let last = *angles.last()?;
+    let span = first + 360.0 - last;
+    if span <= 0.0 {
+        return None;
+    }
+
+    let offset = if x > last { x - last } else { x + 360.0 - last };
+    Some((angles.len() - 1, 0, (offset / span).clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+
+    #[test]
+    fn c0_stays_put_as_the_fixture_tilts() {
+        // Bilateral profile, brightest at C0 and half as bright at C90
+        let mut profile = IesProfile::parse(
+            "IESNA:LM-63-2002\nTILT=NONE\n1 1000 1 2 3 1 1 0 0 0\n1 1 10\n\
+             0 90\n0 90 180\n100 100\n50 50\n10 10\n",
+        )
+        .unwrap();
+
+        for tilt in [0.0_f32, 20.0, 30.0, 60.0] {
+            let axis = Quat::from_rotation_x(tilt.to_radians()) * Vec3::NEG_Y;
+            let towards_c0 = (axis + Vec3::X).normalize();
+
+            profile.rotation = 0.0;
+            assert!((profile.evaluate(axis, towards_c0) - 1.0).abs() < 1e-4);
+            profile.rotation = 90.0;
+            assert!((profile.evaluate(axis, towards_c0) - 0.5).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod ies;
+pub mod obj;
+
+pub use ies::IesProfile;
+pub use obj::{ObjFace, ObjGroup, ObjModel, ObjVertex};
@@ -0,0 +1,305 @@
+use crate::error::{RaystError, Result};
+use glam::{Vec2, Vec3};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjVertex {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjFace {
+    pub vertices: [ObjVertex; 3],
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjGroup {
+    pub name: String,
+    pub material: Option<String>,
+    pub faces: Vec<ObjFace>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjModel {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    // Per-position colors from the `v x y z r g b` extension, empty when
+    // the file has none
+    pub colors: Vec<Vec3>,
+    pub groups: Vec<ObjGroup>,
+}
+
+impl ObjModel {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+            .map_err(|msg| RaystError::InvalidInput(format!("{}: {}", path.display(), msg)))
+    }
+
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let mut model = ObjModel::default();
+        let mut group_name = String::from("default");
+        let mut material: Option<String> = None;
+
+        for (line_index, raw_line) in content.lines().enumerate() {
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            let mut tokens = line.split_whitespace();
+
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+
+            let error = |msg: &str| format!("line {}: {}", line_index + 1, msg);
+
+            match keyword {
+                "v" => {
+                    let values: Vec<&str> = tokens.collect();
+                    let [x, y, z] =
+                        parse_floats::<3>(values.iter().copied()).map_err(|e| error(&e))?;
+                    model.positions.push(Vec3::new(x, y, z));
+
+                    if values.len() >= 6 {
+                        let [r, g, b] = parse_floats::<3>(values[3..].iter().copied())
+                            .map_err(|e| error(&e))?;
+                        model.colors.resize(model.positions.len() - 1, Vec3::ONE);
+                        model.colors.push(Vec3::new(r, g, b));
+                    }
+                }
+                "vn" => {
+                    let [x, y, z] = parse_floats::<3>(tokens).map_err(|e| error(&e))?;
+                    model.normals.push(Vec3::new(x, y, z).normalize_or_zero());
+                }
+                "vt" => {
+                    let [u, v] = parse_floats::<2>(tokens).map_err(|e| error(&e))?;
+                    model.uvs.push(Vec2::new(u, v));
+                }
+                "f" => {
+                    let vertices = tokens
+                        .map(|token| model.parse_vertex(token))
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(|e| error(&e))?;
+
+                    if vertices.len() < 3 {
+                        return Err(error("face needs at least 3 vertices"));
+                    }
+
+                    let group = model.current_group(&group_name, &material);
+                    for i in 1..vertices.len() - 1 {
+                        group.faces.push(ObjFace {
+                            vertices: [vertices[0], vertices[i], vertices[i + 1]],
+                        });
+                    }
+                }
+                "g" | "o" => {
+                    group_name = tokens.collect::<Vec<_>>().join(" ");
+                    if group_name.is_empty() {
+                        group_name = String::from("default");
+                    }
+                }
+                "usemtl" => {
+                    let name = tokens
+                        .next()
+                        .ok_or_else(|| error("usemtl needs a material name"))?;
+                    material = Some(name.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        model.groups.retain(|group| !group.faces.is_empty());
+        if !model.colors.is_empty() {
+            model.colors.resize(model.positions.len(), Vec3::ONE);
+        }
+
+        if model.groups.is_empty() {
+            return Err(String::from("no faces found"));
+        }
+
+        Ok(model)
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.groups.iter().map(|group| group.faces.len()).sum()
+    }
+
+    // First `usemtl` name that `is_known` doesn't accept
+    pub fn unmapped_material(&self, is_known: impl Fn(&str) -> bool) -> Option<&str> {
+        self.groups
+            .iter()
+            .filter_map(|group| group.material.as_deref())
+            .find(|name| !is_known(name))
+    }
+
+    fn current_group(&mut self, name: &str, material: &Option<String>) -> &mut ObjGroup {
+        let matches = self
+            .groups
+            .last()
+            .is_some_and(|group| group.name == name && group.material == *material);
+
+        if !matches {
+            self.groups.push(ObjGroup {
+                name: name.to_string(),
+                material: material.clone(),
+                faces: Vec::new(),
+            });
+        }
+
+        self.groups.last_mut().unwrap()
+    }
+
+    fn parse_vertex(&self, token: &str) -> std::result::Result<ObjVertex, String> {
+        let mut parts = token.split('/');
+
+        let position = parts
+            .next()
+            .ok_or_else(|| format!("invalid face vertex '{}'", token))
+            .and_then(|p| resolve_index(p, self.positions.len()))?;
+
+        let uv = match parts.next() {
+            Some(p) if !p.is_empty() => Some(resolve_index(p, self.uvs.len())?),
+            _ => None,
+        };
+
+        let normal = match parts.next() {
+            Some(p) if !p.is_empty() => Some(resolve_index(p, self.normals.len())?),
+            _ => None,
+        };
+
+        Ok(ObjVertex {
+            position,
+            uv,
+            normal,
+        })
+    }
+}
+
+fn parse_floats<'a, const N: usize>(
+    tokens: impl Iterator<Item = &'a str>,
+) -> std::result::Result<[f32; N], String> {
+    let mut values = [0.0; N];
+    let mut tokens = tokens;
+
+    for value in values.iter_mut() {
+        let token = tokens
+            .next()
+            .ok_or_else(|| format!("expected {} values", N))?;
+        *value = token
+            .parse()
+            .map_err(|_| format!("invalid number '{}'", token))?;
+    }
+
+    Ok(values)
+}
+
+fn resolve_index(token: &str, count: usize) -> std::result::Result<usize, String> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| format!("invalid index '{}'", token))?;
+
+    let resolved = if index > 0 {
+        index - 1
+    } else if index < 0 {
+        count as i64 + index
+    } else {
+        return Err(String::from("index 0 is not valid in OBJ files"));
+    };
+
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {} out of range", index));
+    }
+
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(faces: &str) -> ObjModel {
+        ObjModel::parse(&format!(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             vn 0 0 2\n{}",
+            faces
+        ))
+        .unwrap()
+    }
+
+    fn positions(model: &ObjModel) -> Vec<[usize; 3]> {
+        model.groups[0]
+            .faces
+            .iter()
+            .map(|face| face.vertices.map(|vertex| vertex.position))
+            .collect()
+    }
+
+    #[test]
+    fn polygons_are_fanned_into_triangles() {
+        let model = square("f 1 2 3 4\n");
+        assert_eq!(model.face_count(), 2);
+        assert_eq!(positions(&model), [[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_latest_element() {
+        let model = square("f -4 -3 -2 -1\n");
+        assert_eq!(positions(&model), [[0, 1, 2], [0, 2, 3]]);
+
+        let model = square("f -4/-4/-1 -3/-3/-1 -2/-2/-1\n");
+        let vertex = model.groups[0].faces[0].vertices[1];
+        assert_eq!((vertex.uv, vertex.normal), (Some(1), Some(0)));
+    }
+
+    #[test]
+    fn vertex_forms_with_uvs_and_normals() {
+        let vertices = |face: &str| square(face).groups[0].faces[0].vertices[2];
+
+        let position_only = vertices("f 1 2 3\n");
+        assert_eq!((position_only.uv, position_only.normal), (None, None));
+        let with_uv = vertices("f 1/1 2/2 3/3\n");
+        assert_eq!((with_uv.uv, with_uv.normal), (Some(2), None));
+        let with_normal = vertices("f 1//1 2//1 3//1\n");
+        assert_eq!((with_normal.uv, with_normal.normal), (None, Some(0)));
+        let with_both = vertices("f 1/1/1 2/2/1 3/3/1\n");
+        assert_eq!((with_both.uv, with_both.normal), (Some(2), Some(0)));
+
+        // Normals are unit length whatever the file stores
+        assert_eq!(square("f 1 2 3\n").normals[0], Vec3::Z);
+    }
+
+    #[test]
+    fn bad_faces_report_the_line() {
+        for (face, message) in [
+            ("f 1 2\n", "line 10: face needs at least 3 vertices"),
+            ("f 1 2 5\n", "line 10: index 5 out of range"),
+            ("f 0 1 2\n", "line 10: index 0 is not valid in OBJ files"),
+            ("f -5 1 2\n", "line 10: index -5 out of range"),
+            ("f 1/9 2 3\n", "line 10: index 9 out of range"),
+            ("f 1 2 x\n", "line 10: invalid index 'x'"),
+        ] {
+            let content = format!("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\n\n\n\n\n{}", face);
+            assert_eq!(ObjModel::parse(&content).unwrap_err(), message);
+        }
+    }
+
+    #[test]
+    fn usemtl_splits_groups_and_reports_unknown_names() {
+        let model = square("usemtl red\nf 1 2 3\nusemtl blue\nf 1 3 4\n");
+        let materials: Vec<_> = model
+            .groups
+            .iter()
+            .map(|group| group.material.as_deref())
+            .collect();
+        assert_eq!(materials, [Some("red"), Some("blue")]);
+
+        assert_eq!(model.unmapped_material(|name| name == "red"), Some("blue"));
+        assert_eq!(model.unmapped_material(|_| true), None);
+
+        let error = ObjModel::parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl\nf 1 2 3\n");
+        assert_eq!(error.unwrap_err(), "line 4: usemtl needs a material name");
+    }
+}
@@ -1,4 +1,3 @@
-use glam::Vec3;
 use rayst::*;
 use std::env;
 
@@ -24,81 +23,65 @@ fn run(config_path: &str) -> Result<()> {
     println!("Loading scene from {}", config_path);
 
     let mut scene = Scene::new(&config);
-
+    let mut builder = ObjectBuilder::new(&config)?;
     for obj_config in &config.objects {
-        let object: Box<dyn crate::geometry::Primitive> = match obj_config {
-            ObjectConfig::Sphere {
-                position,
-                radius,
-                material,
-            } => {
-                let mat = config.to_material(material);
-                Box::new(Sphere::new(Vec3::from_array(*position), *radius, mat))
-            }
-            ObjectConfig::Plane {
-                position,
-                normal,
-                material,
-            } => {
-                let mat = config.to_material(material);
-                Box::new(Plane::new(
-                    Vec3::from_array(*position),
-                    Vec3::from_array(*normal),
-                    mat,
-                ))
-            }
-            ObjectConfig::Cube {
-                position,
-                size,
-                rotation,
-                material,
-            } => {
-                let mat = config.to_material(material);
-                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
-                Box::new(Cube::new(Vec3::from_array(*position), rot, *size, mat))
-            }
-            ObjectConfig::Pyramid {
-                position,
-                base_size,
-                height,
-                rotation,
-                material,
-            } => {
-                let mat = config.to_material(material);
-                let rot = Vec3::from_array(rotation.unwrap_or([0.0, 0.0, 0.0]));
-                Box::new(Pyramid::new(
-                    Vec3::from_array(*position),
-                    *base_size,
-                    *height,
-                    rot,
-                    mat,
-                ))
-            }
-        };
-        scene.add_object(object);
+        for object in builder.build(obj_config)? {
+            scene.add_object(object);
+        }
     }
 
     for light_config in &config.lights {
-        scene.add_light(PointLight::new(
-            Vec3::from_array(light_config.position),
-            Vec3::from_array(light_config.color.map(|c| c as f32)),
-            light_config.intensity,
-        ));
+        if light_config.is_area() {
+            scene.add_object(config.to_area_light(light_config)?);
+        } else {
+            scene.add_light(config.to_light(light_config)?);
+        }
     }
 
-    let camera = config.to_camera();
+    scene.environment = config.to_environment()?;
+    if let Some(sun) = config.to_sky_sun()? {
+        scene.add_light(sun);
+    }
+    scene.build_bvh();
+
+    let camera = config.to_camera()?;
+
+    let mut raytracer = Raytracer::new(camera, config.to_integrator()?);
 
-    let raytracer = Raytracer::new(camera);
+    let aov_config = config.render.aovs.as_ref();
+    let multilayer = aov_config.is_some_and(|aovs| aovs.is_multilayer());
+    raytracer.aovs = aov_config.map(|aovs| aovs.enabled()).unwrap_or_default();
 
-    let img = raytracer.render(
+    if multilayer && OutputFormat::from_path(&config.render.output_file) != OutputFormat::OpenExr {
+        return Err(RaystError::InvalidInput(String::from(
+            "Multi-layer AOVs require an .exr output_file",
+        )));
+    }
+
+    let output = raytracer.render(
         &scene,
         config.render.width,
         config.render.height,
         config.render.antialiasing,
-    );
+    )?;
+
+    if multilayer {
+        save_multilayer_exr(&output.beauty, &output.aovs, &config.render.output_file)?;
+        println!("Saved Rendered to: {}", config.render.output_file);
+        return Ok(());
+    }
 
-    img.unwrap().save(&config.render.output_file)?;
+    let tone_mapper = config.to_tone_mapper()?;
+    save_image(
+        &output.beauty,
+        &config.render.output_file,
+        raytracer.integrator.is_radiance().then_some(&tone_mapper),
+    )?;
     println!("Saved Rendered to: {}", config.render.output_file);
 
+    for path in save_aovs(&output.aovs, &config.render.output_file)? {
+        println!("Saved AOV to: {}", path.display());
+    }
+
     Ok(())
 }
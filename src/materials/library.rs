@@ -0,0 +1,44 @@
+use crate::error::{RaystError, Result};
+use crate::materials::Material;
+use std::collections::HashMap;
+
+// Named materials shared between scene objects. Lookups hand out clones,
+// which share any textures.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialLibrary {
+    materials: HashMap<String, Material>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, material: Material) {
+        self.materials.insert(name.into(), material);
+    }
+
+    pub fn get(&self, name: &str) -> Result<Material> {
+        self.materials.get(name).cloned().ok_or_else(|| {
+            let names = self.names();
+            RaystError::InvalidInput(if names.is_empty() {
+                format!(
+                    "Unknown material '{}', the scene defines no materials",
+                    name
+                )
+            } else {
+                format!(
+                    "Unknown material '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.materials.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
@@ -1,4 +1,17 @@
-use glam::Vec3;
+use crate::math::{Intersection, sampling};
+use crate::textures::Texture;
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+const MIN_ROUGHNESS: f32 = 0.03;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BsdfSample {
+    pub direction: Vec3,
+    pub value: Vec3,
+    pub pdf: f32,
+}
 
 #[derive(Debug, Clone)]
 pub struct Material {
@@ -9,6 +22,20 @@ pub struct Material {
 
     pub emission: Vec3,
     pub ior: f32,
+    pub transparency: f32,
+    pub absorption: f32,
+
+    // Textures override the constant parameter above at each hit point;
+    // scalar parameters read the red channel
+    pub albedo_texture: Option<Arc<dyn Texture>>,
+    pub metallic_texture: Option<Arc<dyn Texture>>,
+    pub roughness_texture: Option<Arc<dyn Texture>>,
+    pub ao_texture: Option<Arc<dyn Texture>>,
+
+    // Tangent space normal map, and a height map read from the red channel
+    pub normal_texture: Option<Arc<dyn Texture>>,
+    pub bump_texture: Option<Arc<dyn Texture>>,
+    pub bump_strength: f32,
 }
 
 impl Material {
@@ -20,6 +47,24 @@ impl Material {
             ao: 1.0,
             emission: Vec3::ZERO,
             ior: 1.0,
+            transparency: 0.0,
+            absorption: 0.0,
+            albedo_texture: None,
+            metallic_texture: None,
+            roughness_texture: None,
+            ao_texture: None,
+            normal_texture: None,
+            bump_texture: None,
+            bump_strength: 1.0,
+        }
+    }
+
+    pub fn dielectric(color: Vec3, ior: f32, absorption: f32) -> Self {
+        Self {
+            ior: ior.max(1.0),
+            transparency: 1.0,
+            absorption: absorption.max(0.0),
+            ..Self::new(color, 0.0, 0.0)
         }
     }
 
@@ -43,6 +88,102 @@ impl Material {
         Self::new(Vec3::new(0.95, 0.64, 0.54), 1.0, 0.2)
     }
 
+    pub fn glass() -> Self {
+        Self::dielectric(Vec3::new(0.95, 0.98, 0.96), 1.5, 0.0)
+    }
+
+    pub fn water() -> Self {
+        Self::dielectric(Vec3::new(0.75, 0.9, 0.95), 1.333, 0.5)
+    }
+
+    pub fn is_textured(&self) -> bool {
+        self.albedo_texture.is_some()
+            || self.metallic_texture.is_some()
+            || self.roughness_texture.is_some()
+            || self.ao_texture.is_some()
+            || self.normal_texture.is_some()
+            || self.bump_texture.is_some()
+    }
+
+    pub fn apply_textures(&mut self, uv: Vec2, point: Vec3) {
+        if let Some(texture) = &self.albedo_texture {
+            self.albedo = texture.value(uv, point).max(Vec3::ZERO);
+        }
+        if let Some(texture) = &self.metallic_texture {
+            self.metallic = texture.value(uv, point).x.clamp(0.0, 1.0);
+        }
+        if let Some(texture) = &self.roughness_texture {
+            self.roughness = texture.value(uv, point).x.clamp(0.0, 1.0);
+        }
+        if let Some(texture) = &self.ao_texture {
+            self.ao = texture.value(uv, point).x.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn shading_normal(&self, hit: &Intersection) -> Vec3 {
+        const BUMP_DELTA: f32 = 1e-3;
+
+        // Start from the interpolated normal of smooth meshes
+        let normal = hit.shading_normal;
+        if self.normal_texture.is_none() && self.bump_texture.is_none() {
+            return normal;
+        }
+
+        // Re-orthogonalize the frame, interpolated tangents drift off the normal
+        let tangent = (hit.tangent - normal * normal.dot(hit.tangent))
+            .try_normalize()
+            .unwrap_or_else(|| sampling::orthonormal_basis(normal).0);
+        let mut bitangent = normal.cross(tangent);
+        if bitangent.dot(hit.bitangent) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let mut shading = normal;
+        if let Some(texture) = &self.normal_texture {
+            let local = texture.value(hit.uv, hit.point) * 2.0 - Vec3::ONE;
+            shading = (tangent * local.x + bitangent * local.y + normal * local.z)
+                .try_normalize()
+                .unwrap_or(normal);
+        }
+
+        // Forward differences of the height along u and v. Solid textures are
+        // stepped along the matching world space tangent instead.
+        if let Some(texture) = &self.bump_texture {
+            let height = |uv: Vec2, point: Vec3| texture.value(uv, point).x;
+            let base = height(hit.uv, hit.point);
+            let du = height(
+                hit.uv + Vec2::X * BUMP_DELTA,
+                hit.point + tangent * BUMP_DELTA,
+            ) - base;
+            let dv = height(
+                hit.uv + Vec2::Y * BUMP_DELTA,
+                hit.point + bitangent * BUMP_DELTA,
+            ) - base;
+
+            let offset = (tangent * du + bitangent * dv) / BUMP_DELTA * self.bump_strength;
+            shading = (shading - offset).try_normalize().unwrap_or(shading);
+        }
+
+        shading
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission.max_element() > 0.0
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.transparency > 0.0
+    }
+
+    pub fn transmittance(&self, distance: f32) -> Vec3 {
+        if self.absorption > 0.0 {
+            let sigma = (Vec3::ONE - self.albedo).max(Vec3::ZERO) * self.absorption;
+            (-sigma * distance).exp()
+        } else {
+            Vec3::ONE
+        }
+    }
+
     pub fn get_f0(&self) -> Vec3 {
         if self.metallic > 0.5 {
             self.albedo
@@ -59,6 +200,102 @@ impl Material {
         )
     }
 
+    pub fn evaluate_brdf(&self, n: Vec3, v: Vec3, l: Vec3) -> Vec3 {
+        let roughness = self.roughness.max(MIN_ROUGHNESS);
+        let h = (v + l).normalize();
+
+        let n_dot_v = n.dot(v).max(0.0);
+        let n_dot_l = n.dot(l).max(0.0);
+        let n_dot_h = n.dot(h).max(0.0);
+        let v_dot_h = v.dot(h).max(0.0);
+
+        let f0 = self.get_f0();
+        let ndf = Self::distribution_ggx(n_dot_h, roughness);
+        let g = Self::geometry_smith(n_dot_v, n_dot_l, roughness);
+        let f = Self::fresnel_schlick(v_dot_h, f0);
+
+        let numerator = ndf * g * f;
+        let denominator = 4.0 * n_dot_v * n_dot_l + 0.0001;
+        let specular = numerator / denominator;
+
+        let ks = f;
+        let mut kd = Vec3::ONE - ks;
+        kd *= 1.0 - self.metallic;
+
+        let diffuse = self.albedo / PI;
+
+        kd * diffuse + specular
+    }
+
+    pub fn sample_brdf(&self, n: Vec3, v: Vec3) -> Option<BsdfSample> {
+        let n_dot_v = n.dot(v);
+        if n_dot_v <= 0.0 {
+            return None;
+        }
+
+        let direction = if fastrand::f32() < self.specular_probability(n_dot_v) {
+            let roughness = self.roughness.max(MIN_ROUGHNESS);
+            let a = roughness * roughness;
+            let a2 = a * a;
+
+            let u1 = fastrand::f32();
+            let phi = 2.0 * PI * fastrand::f32();
+            let cos_theta = ((1.0 - u1) / (1.0 + (a2 - 1.0) * u1)).sqrt();
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+            let local_h = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+            let h = sampling::to_world(local_h, n);
+
+            2.0 * v.dot(h) * h - v
+        } else {
+            sampling::to_world(
+                sampling::sample_cosine_hemisphere(fastrand::f32(), fastrand::f32()),
+                n,
+            )
+        };
+
+        if n.dot(direction) <= 0.0 {
+            return None;
+        }
+
+        let pdf = self.brdf_pdf(n, v, direction);
+        (pdf > 0.0).then(|| BsdfSample {
+            direction,
+            value: self.evaluate_brdf(n, v, direction),
+            pdf,
+        })
+    }
+
+    pub fn brdf_pdf(&self, n: Vec3, v: Vec3, l: Vec3) -> f32 {
+        let n_dot_v = n.dot(v);
+        let n_dot_l = n.dot(l);
+        if n_dot_v <= 0.0 || n_dot_l <= 0.0 {
+            return 0.0;
+        }
+
+        let roughness = self.roughness.max(MIN_ROUGHNESS);
+        let h = (v + l).normalize();
+        let n_dot_h = n.dot(h).max(0.0);
+        let v_dot_h = v.dot(h).max(1e-6);
+
+        let specular_pdf = Self::distribution_ggx(n_dot_h, roughness) * n_dot_h / (4.0 * v_dot_h);
+        let diffuse_pdf = n_dot_l / PI;
+
+        let p = self.specular_probability(n_dot_v);
+        p * specular_pdf + (1.0 - p) * diffuse_pdf
+    }
+
+    fn specular_probability(&self, n_dot_v: f32) -> f32 {
+        let specular = Self::fresnel_schlick(n_dot_v, self.get_f0()).element_sum() / 3.0;
+        let diffuse = (1.0 - specular) * (1.0 - self.metallic) * self.albedo.element_sum() / 3.0;
+
+        if diffuse <= 0.0 {
+            1.0
+        } else {
+            (specular / (specular + diffuse)).clamp(0.1, 0.9)
+        }
+    }
+
     pub fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
         let a = roughness * roughness;
         let a2 = a * a;
@@ -66,7 +303,7 @@ impl Material {
 
         let num = a2;
         let denom = n_dot_h2 * (a2 - 1.0) + 1.0;
-        let denom = std::f32::consts::PI * denom * denom;
+        let denom = PI * denom * denom;
 
         num / denom
     }
@@ -91,4 +328,20 @@ impl Material {
     pub fn fresnel_schlick(cos_theta: f32, f0: Vec3) -> Vec3 {
         f0 + (Vec3::ONE - f0) * (1.0 - cos_theta).powf(5.0)
     }
+
+    pub fn fresnel_dielectric(cos_i: f32, eta_i: f32, eta_t: f32) -> f32 {
+        let cos_i = cos_i.clamp(0.0, 1.0);
+        let sin_t = eta_i / eta_t * (1.0 - cos_i * cos_i).max(0.0).sqrt();
+
+        if sin_t >= 1.0 {
+            return 1.0;
+        }
+
+        let cos_t = (1.0 - sin_t * sin_t).max(0.0).sqrt();
+
+        let r_parallel = (eta_t * cos_i - eta_i * cos_t) / (eta_t * cos_i + eta_i * cos_t);
+        let r_perpendicular = (eta_i * cos_i - eta_t * cos_t) / (eta_i * cos_i + eta_t * cos_t);
+
+        (r_parallel * r_parallel + r_perpendicular * r_perpendicular) / 2.0
+    }
 }
@@ -1,3 +1,5 @@
+pub mod library;
 pub mod material;
 
-pub use material::Material;
+pub use library::MaterialLibrary;
+pub use material::{BsdfSample, Material};
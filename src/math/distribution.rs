@@ -0,0 +1,59 @@
+#[derive(Debug, Clone, Default)]
+pub struct Distribution1D {
+    weights: Vec<f32>,
+    cdf: Vec<f32>,
+    total: f32,
+}
+
+impl Distribution1D {
+    pub fn new(weights: &[f32]) -> Self {
+        let weights: Vec<f32> = weights.iter().map(|w| w.max(0.0)).collect();
+
+        let mut cdf = Vec::with_capacity(weights.len());
+        let mut total = 0.0;
+        for weight in &weights {
+            total += weight;
+            cdf.push(total);
+        }
+
+        Self {
+            weights,
+            cdf,
+            total,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total <= 0.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn total(&self) -> f32 {
+        self.total
+    }
+
+    pub fn sample(&self, u: f32) -> Option<(usize, f32)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let target = u * self.total;
+        let index = self
+            .cdf
+            .partition_point(|&c| c <= target)
+            .min(self.cdf.len() - 1);
+
+        Some((index, self.pdf(index)))
+    }
+
+    pub fn pdf(&self, index: usize) -> f32 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.weights.get(index).copied().unwrap_or(0.0) / self.total
+        }
+    }
+}
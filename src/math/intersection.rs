@@ -1,21 +1,65 @@
 use crate::materials::Material;
-use glam::Vec3;
+use crate::math::sampling;
+use glam::{Vec2, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct Intersection {
     pub distance: f32,
     pub point: Vec3,
+    // Geometric normal, used to offset secondary rays off the surface
     pub normal: Vec3,
+    // Normal used for shading, perturbed by normal and bump maps
+    pub shading_normal: Vec3,
+    // Surface directions of increasing u and v
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
     pub material: Material,
+    pub uv: Vec2,
+    // Interpolated vertex color, multiplied into the albedo
+    pub color: Vec3,
+    // Albedo before the vertex color is applied, which identifies the material
+    pub base_albedo: Vec3,
+    pub object_id: usize,
 }
 
 impl Intersection {
     pub fn new(distance: f32, point: Vec3, normal: Vec3, material: Material) -> Self {
+        let (tangent, bitangent) = sampling::orthonormal_basis(normal);
+        let base_albedo = material.albedo;
+
         Self {
             distance,
             point,
             normal,
+            shading_normal: normal,
+            tangent,
+            bitangent,
             material,
+            uv: Vec2::ZERO,
+            color: Vec3::ONE,
+            base_albedo,
+            object_id: 0,
         }
     }
+
+    pub fn with_uv(mut self, uv: Vec2) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_shading_normal(mut self, shading_normal: Vec3) -> Self {
+        self.shading_normal = shading_normal;
+        self
+    }
+
+    pub fn with_color(mut self, color: Vec3) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_tangents(mut self, tangent: Vec3, bitangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self.bitangent = bitangent;
+        self
+    }
 }
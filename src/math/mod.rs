@@ -1,5 +1,13 @@
+pub mod aabb;
+pub mod distribution;
 pub mod intersection;
+pub mod polynomial;
 pub mod ray;
+pub mod sampling;
+pub mod transform;
 
+pub use aabb::Aabb;
+pub use distribution::Distribution1D;
 pub use intersection::Intersection;
 pub use ray::Ray;
+pub use transform::{Transform, euler_rotation};
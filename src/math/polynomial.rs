@@ -0,0 +1,245 @@
+// Real roots of low degree polynomials in increasing order, after Schwarze's
+// solvers in Graphics Gems I. Computed in f64 since the quartic loses too
+// much precision in f32.
+
+const EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+// a x^2 + b x + c = 0
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return if is_zero(b) { Vec::new() } else { vec![-c / b] };
+    }
+
+    let p = b / (2.0 * a);
+    let q = c / a;
+    let discriminant = p * p - q;
+
+    if is_zero(discriminant) {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        Vec::new()
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![-p - sqrt_d, -p + sqrt_d]
+    }
+}
+
+// a x^3 + b x^2 + c x + d = 0
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_quadratic(b, c, d);
+    }
+
+    // Substitute x = y - A/3 to eliminate the quadratic term
+    let (a, b, c) = (b / a, c / a, d / a);
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let mut roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + std::f64::consts::FRAC_PI_3).cos(),
+            -t * (phi - std::f64::consts::FRAC_PI_3).cos(),
+        ]
+    } else {
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+
+    for root in &mut roots {
+        *root -= a / 3.0;
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// a x^4 + b x^3 + c x^2 + d x + e = 0. A repeated root, such as where a ray
+// grazes a torus, is reported at least once, possibly twice with tiny
+// differences. Distinct roots very close together may merge or both be
+// missed, so a ray that nearly grazes a surface can miss it.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_cubic(b, c, d, e);
+    }
+
+    // Substitute x = y - A/4 to eliminate the cubic term
+    let (qa, qb, qc, qd) = (b / a, c / a, d / a, e / a);
+    let sq_a = qa * qa;
+    let p = -3.0 / 8.0 * sq_a + qb;
+    let q = sq_a * qa / 8.0 - qa * qb / 2.0 + qc;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * qb / 16.0 - qa * qc / 4.0 + qd;
+
+    let mut roots = if is_zero(r) {
+        // y (y^3 + p y + q) = 0
+        let mut roots = solve_cubic(1.0, 0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Any real root z of the resolvent cubic makes the quartic a
+        // difference of squares, (y^2 + z)^2 - (v y - w)^2. The largest one
+        // keeps v^2 = 2z - p from going negative.
+        let Some(&z) = solve_cubic(1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0).last() else {
+            return Vec::new();
+        };
+
+        let v = (2.0 * z - p).max(0.0).sqrt();
+        // w^2 = z^2 - r as well, but q / 2v doesn't lose its sign or cancel
+        let w = if v > EPSILON {
+            q / (2.0 * v)
+        } else {
+            (z * z - r).max(0.0).sqrt()
+        };
+
+        // Repeated roots leave a factor's discriminant at zero, which
+        // rounding can nudge below it, so accept slightly negative ones
+        let mut roots = Vec::with_capacity(4);
+        for (b, c) in [(-v, z + w), (v, z - w)] {
+            let half = b / 2.0;
+            let discriminant = half * half - c;
+            if discriminant >= -1e-6 * (half * half + c.abs()) {
+                let sqrt_d = discriminant.max(0.0).sqrt();
+                roots.extend([-half - sqrt_d, -half + sqrt_d]);
+            }
+        }
+        roots
+    };
+
+    let f = |x: f64| (((a * x + b) * x + c) * x + d) * x + e;
+    let df = |x: f64| ((4.0 * a * x + 3.0 * b) * x + 2.0 * c) * x + d;
+    let scale = |x: f64| {
+        let x = x.abs();
+        (((a.abs() * x + b.abs()) * x + c.abs()) * x + d.abs()) * x + e.abs()
+    };
+
+    // Resubstitute, then polish with Newton steps on the original polynomial
+    // as long as they bring it closer to zero
+    for root in &mut roots {
+        let mut x = *root - qa / 4.0;
+        for _ in 0..4 {
+            let slope = df(x);
+            if slope.abs() <= EPSILON {
+                break;
+            }
+            let next = x - f(x) / slope;
+            if f(next).abs() >= f(x).abs() {
+                break;
+            }
+            x = next;
+        }
+        *root = x;
+    }
+
+    // Drop candidates that aren't roots at all, which a forgiving
+    // discriminant lets through when two roots are nearly complex
+    roots.retain(|&x| f(x).abs() <= 1e-9 * scale(x));
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Coefficients of (x - r0)(x - r1)(x - r2)(x - r3)
+    fn quartic_with_roots([r0, r1, r2, r3]: [f64; 4]) -> Vec<f64> {
+        let s1 = r0 + r1 + r2 + r3;
+        let s2 = r0 * r1 + r0 * r2 + r0 * r3 + r1 * r2 + r1 * r3 + r2 * r3;
+        let s3 = r0 * r1 * r2 + r0 * r1 * r3 + r0 * r2 * r3 + r1 * r2 * r3;
+        let s4 = r0 * r1 * r2 * r3;
+        solve_quartic(1.0, -s1, s2, -s3, s4)
+    }
+
+    fn assert_roots(found: &[f64], expected: &[f64], tolerance: f64) {
+        for root in expected {
+            assert!(
+                found.iter().any(|x| (x - root).abs() <= tolerance),
+                "missing root {} in {:?}",
+                root,
+                found
+            );
+        }
+        for x in found {
+            assert!(
+                expected.iter().any(|root| (x - root).abs() <= tolerance),
+                "unexpected root {} in {:?}",
+                x,
+                found
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_and_cubic_roots() {
+        assert_roots(&solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0], 1e-12);
+        assert_roots(&solve_quadratic(1.0, -2.0, 1.0), &[1.0], 1e-12);
+        assert_roots(&solve_quadratic(0.0, 2.0, -4.0), &[2.0], 1e-12);
+        assert!(solve_quadratic(1.0, 0.0, 1.0).is_empty());
+
+        assert_roots(&solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0], 1e-9);
+        assert_roots(&solve_cubic(2.0, 0.0, 0.0, -2.0), &[1.0], 1e-9);
+        assert_roots(&solve_cubic(1.0, -4.0, 5.0, -2.0), &[1.0, 2.0], 1e-6);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        assert_roots(
+            &quartic_with_roots([1.0, 2.0, 3.0, 4.0]),
+            &[1.0, 2.0, 3.0, 4.0],
+            1e-9,
+        );
+        assert_roots(
+            &quartic_with_roots([-7.5, -0.25, 0.0, 12.0]),
+            &[-7.5, -0.25, 0.0, 12.0],
+            1e-9,
+        );
+        // (x^2 + 1)(x^2 + 4)
+        assert!(solve_quartic(1.0, 0.0, 5.0, 0.0, 4.0).is_empty());
+    }
+
+    #[test]
+    fn quartic_through_a_torus_hole() {
+        // A ray along X from x = -5 crosses a torus with R = 2, r = 0.5 at
+        // x = -2.5, -1.5, 1.5 and 2.5
+        assert_roots(
+            &quartic_with_roots([2.5, 3.5, 6.5, 7.5]),
+            &[2.5, 3.5, 6.5, 7.5],
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn quartic_repeated_roots() {
+        assert_roots(
+            &quartic_with_roots([1.0, 1.0, 3.0, 5.0]),
+            &[1.0, 3.0, 5.0],
+            1e-6,
+        );
+        assert_roots(
+            &quartic_with_roots([-1.0, -1.0, 2.0, 2.0]),
+            &[-1.0, 2.0],
+            1e-6,
+        );
+        assert_roots(&quartic_with_roots([3.0, 3.0, 3.0, 7.0]), &[3.0, 7.0], 1e-4);
+
+        // Two double roots used to leave the factors with slightly negative
+        // discriminants and lose every root
+        let (x0, x1) = (9.356300257627737, -7.404542851007667);
+        assert_roots(&quartic_with_roots([x0, x0, x1, x1]), &[x0, x1], 1e-6);
+    }
+}
@@ -0,0 +1,90 @@
+use glam::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+pub fn orthonormal_basis(w: Vec3) -> (Vec3, Vec3) {
+    let up = if w.y.abs() < 0.9 { Vec3::Y } else { Vec3::X };
+    let u = w.cross(up).normalize();
+    let v = u.cross(w);
+    (u, v)
+}
+
+pub fn to_world(local: Vec3, w: Vec3) -> Vec3 {
+    let (u, v) = orthonormal_basis(w);
+    u * local.x + v * local.y + w * local.z
+}
+
+pub fn sample_uniform_sphere(u1: f32, u2: f32) -> Vec3 {
+    let z = 1.0 - 2.0 * u1;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+pub fn sample_cosine_hemisphere(u1: f32, u2: f32) -> Vec3 {
+    let r = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt())
+}
+
+pub fn power_heuristic(pdf_a: f32, pdf_b: f32) -> f32 {
+    let a = pdf_a * pdf_a;
+    let b = pdf_b * pdf_b;
+    if a + b <= 0.0 { 0.0 } else { a / (a + b) }
+}
+
+pub fn sample_cone(u1: f32, u2: f32, cos_theta_max: f32) -> Vec3 {
+    let cos_theta = 1.0 - u1 * (1.0 - cos_theta_max);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+pub fn cone_pdf(cos_theta_max: f32) -> f32 {
+    1.0 / (2.0 * PI * (1.0 - cos_theta_max)).max(1e-8)
+}
+
+pub fn sample_concentric_disk(u1: f32, u2: f32) -> Vec2 {
+    let offset = Vec2::new(2.0 * u1 - 1.0, 2.0 * u2 - 1.0);
+    if offset == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+
+    let (r, theta) = if offset.x.abs() > offset.y.abs() {
+        (offset.x, PI / 4.0 * (offset.y / offset.x))
+    } else {
+        (offset.y, PI / 2.0 - PI / 4.0 * (offset.x / offset.y))
+    };
+
+    Vec2::new(theta.cos(), theta.sin()) * r
+}
+
+pub fn sample_regular_polygon(u1: f32, u2: f32, u3: f32, sides: u32, rotation: f32) -> Vec2 {
+    let sides = sides.max(3);
+    let blade = ((u1 * sides as f32) as u32).min(sides - 1);
+    let step = 2.0 * PI / sides as f32;
+
+    let angle0 = rotation + blade as f32 * step;
+    let angle1 = angle0 + step;
+    let v0 = Vec2::new(angle0.cos(), angle0.sin());
+    let v1 = Vec2::new(angle1.cos(), angle1.sin());
+
+    let (b0, b1) = sample_triangle(u2, u3);
+    v0 * b0 + v1 * b1
+}
+
+pub fn sample_triangle(u1: f32, u2: f32) -> (f32, f32) {
+    let su = u1.sqrt();
+    (1.0 - su, u2 * su)
+}
+
+pub fn area_to_solid_angle(pdf_area: f32, reference: Vec3, point: Vec3, normal: Vec3) -> f32 {
+    let to_point = point - reference;
+    let distance_squared = to_point.length_squared();
+    let cos_theta = normal.dot(-to_point.normalize()).abs();
+
+    if cos_theta <= 1e-6 {
+        0.0
+    } else {
+        pdf_area * distance_squared / cos_theta
+    }
+}
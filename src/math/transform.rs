@@ -0,0 +1,106 @@
+use crate::math::{Aabb, Intersection, Ray};
+use glam::{Mat3, Mat4, Quat, Vec3};
+
+// Rotation from Euler angles in degrees, applied around X, then Y, then Z
+pub fn euler_rotation(degrees: Vec3) -> Mat3 {
+    let radians = degrees * std::f32::consts::PI / 180.0;
+
+    let rot_x = Mat3::from_rotation_x(radians.x);
+    let rot_y = Mat3::from_rotation_y(radians.y);
+    let rot_z = Mat3::from_rotation_z(radians.z);
+
+    rot_z * rot_y * rot_x
+}
+
+// Affine transform from object to world space: scale, then rotate, then translate
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub matrix: Mat4,
+    pub inverse: Mat4,
+    // Set when the scale is the same along every axis
+    pub uniform_scale: Option<f32>,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        matrix: Mat4::IDENTITY,
+        inverse: Mat4::IDENTITY,
+        uniform_scale: Some(1.0),
+    };
+
+    pub fn new(translation: Vec3, rotation: Mat3, scale: Vec3) -> Self {
+        let matrix = Mat4::from_translation(translation)
+            * Mat4::from_mat3(rotation)
+            * Mat4::from_scale(scale);
+        let uniform = scale.abs();
+        let uniform_scale = (uniform.max_element() - uniform.min_element()
+            <= uniform.max_element() * 1e-5)
+            .then_some(uniform.x);
+
+        Self {
+            matrix,
+            inverse: matrix.inverse(),
+            uniform_scale,
+        }
+    }
+
+    pub fn from_quaternion(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self::new(translation, Mat3::from_quat(rotation.normalize()), scale)
+    }
+
+    pub fn point(&self, point: Vec3) -> Vec3 {
+        self.matrix.transform_point3(point)
+    }
+
+    pub fn vector(&self, vector: Vec3) -> Vec3 {
+        self.matrix.transform_vector3(vector)
+    }
+
+    // Normals follow the inverse transpose to stay perpendicular under
+    // non-uniform scale
+    pub fn normal(&self, normal: Vec3) -> Vec3 {
+        (Mat3::from_mat4(self.inverse).transpose() * normal).normalize_or_zero()
+    }
+
+    pub fn inverse_point(&self, point: Vec3) -> Vec3 {
+        self.inverse.transform_point3(point)
+    }
+
+    pub fn inverse_normal(&self, normal: Vec3) -> Vec3 {
+        (Mat3::from_mat4(self.matrix).transpose() * normal).normalize_or_zero()
+    }
+
+    // The ray in object space with a normalized direction, and the factor
+    // that converts object space distances back to world space
+    pub fn inverse_ray(&self, ray: &Ray) -> Option<(Ray, f32)> {
+        let direction = self.inverse.transform_vector3(ray.direction);
+        let length = direction.length();
+        if length <= 0.0 || !length.is_finite() {
+            return None;
+        }
+
+        Some((
+            Ray::new(self.inverse_point(ray.origin), direction / length),
+            1.0 / length,
+        ))
+    }
+
+    pub fn aabb(&self, aabb: &Aabb) -> Aabb {
+        Aabb::from_points(&aabb.corners().map(|corner| self.point(corner)))
+    }
+
+    // Moves a hit found with a ray from `inverse_ray` back to world space
+    pub fn intersection_to_world(
+        &self,
+        mut hit: Intersection,
+        distance_scale: f32,
+    ) -> Intersection {
+        hit.distance *= distance_scale;
+        hit.point = self.point(hit.point);
+        hit.normal = self.normal(hit.normal);
+        hit.shading_normal = self.normal(hit.shading_normal);
+        hit.tangent = self.vector(hit.tangent).normalize_or_zero();
+        hit.bitangent = self.vector(hit.bitangent).normalize_or_zero();
+        hit
+    }
+}
@@ -0,0 +1,177 @@
+use crate::materials::Material;
+use crate::math::Intersection;
+use crate::textures::Texture;
+use glam::Vec3;
+use image::Rgb32FImage;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    MaterialId,
+    Position,
+}
+
+impl Aov {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::Position => "position",
+        }
+    }
+
+    pub fn channels(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::Normal | Aov::Position => &["X", "Y", "Z"],
+            Aov::Albedo => &["R", "G", "B"],
+            Aov::ObjectId | Aov::MaterialId => &["id"],
+        }
+    }
+
+    // Every other pass holds data outside 0..1 that 8-bit formats would clamp
+    pub fn is_color(&self) -> bool {
+        matches!(self, Aov::Albedo)
+    }
+
+    // Identifiers cannot be blended across pixel samples, so they keep the
+    // value of the first sample instead of being averaged.
+    pub fn is_filtered(&self) -> bool {
+        !matches!(self, Aov::ObjectId | Aov::MaterialId)
+    }
+
+    pub fn evaluate(&self, hit: &Intersection) -> Vec3 {
+        match self {
+            Aov::Depth => Vec3::splat(hit.distance),
+            Aov::Normal => hit.shading_normal,
+            Aov::Albedo => hit.material.albedo,
+            Aov::ObjectId => Vec3::splat((hit.object_id + 1) as f32),
+            Aov::MaterialId => Vec3::splat(material_id(&hit.material, hit.base_albedo) as f32),
+            Aov::Position => hit.point,
+        }
+    }
+
+    pub fn background(&self) -> Vec3 {
+        match self {
+            Aov::Depth => Vec3::splat(f32::INFINITY),
+            _ => Vec3::ZERO,
+        }
+    }
+}
+
+pub struct RenderOutput {
+    pub beauty: Rgb32FImage,
+    pub aovs: Vec<(Aov, Rgb32FImage)>,
+}
+
+// Materials have no identity of their own, so identical parameters share an
+// id. The hash is truncated to 24 bits to stay exact in an f32 channel.
+// `albedo` is the material's own, without vertex colors.
+fn material_id(material: &Material, albedo: Vec3) -> u32 {
+    let mut hasher = DefaultHasher::new();
+
+    // Textured parameters vary per hit, identify them by their texture instead
+    let constant = |value: f32, texture: &Option<Arc<dyn Texture>>| {
+        if texture.is_some() { 0.0 } else { value }
+    };
+    let albedo = if material.albedo_texture.is_some() {
+        Vec3::ZERO
+    } else {
+        albedo
+    };
+
+    for value in [
+        albedo.to_array(),
+        material.emission.to_array(),
+        [
+            constant(material.metallic, &material.metallic_texture),
+            constant(material.roughness, &material.roughness_texture),
+            constant(material.ao, &material.ao_texture),
+        ],
+        [material.ior, material.transparency, material.absorption],
+    ]
+    .concat()
+    {
+        value.to_bits().hash(&mut hasher);
+    }
+    material.bump_strength.to_bits().hash(&mut hasher);
+
+    for texture in [
+        &material.albedo_texture,
+        &material.metallic_texture,
+        &material.roughness_texture,
+        &material.ao_texture,
+        &material.normal_texture,
+        &material.bump_texture,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        texture.identity(&mut hasher);
+    }
+
+    ((hasher.finish() & 0x00ff_ffff) as u32).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SceneConfig;
+    use crate::geometry::Triangle;
+    use crate::math::Ray;
+    use crate::scene::Scene;
+
+    fn scene() -> Scene {
+        let config: SceneConfig = toml::from_str(
+            r#"
+            objects = []
+
+            [camera]
+            position = [0.0, 0.0, 0.0]
+            fov = 60.0
+
+            [render]
+            width = 1
+            height = 1
+            max_depth = 1
+            antialiasing = 1
+            output_file = "out.png"
+
+            [background]
+            "#,
+        )
+        .unwrap();
+        Scene::new(&config)
+    }
+
+    #[test]
+    fn vertex_colors_keep_one_material_id() {
+        let mut scene = scene();
+        let triangle = Triangle::new(
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(0.0, 1.0, -1.0),
+            Material::new(Vec3::splat(0.8), 0.0, 0.5),
+        )
+        .with_colors([Vec3::X, Vec3::Y, Vec3::Z]);
+        scene.add_object(Box::new(triangle));
+
+        let hit_at = |x: f32, y: f32| {
+            scene
+                .hit(&Ray::new(Vec3::ZERO, Vec3::new(x, y, -1.0).normalize()))
+                .unwrap()
+        };
+        let (a, b) = (hit_at(-0.5, -0.5), hit_at(0.3, 0.2));
+
+        assert_ne!(a.material.albedo, b.material.albedo);
+        assert_eq!(Aov::MaterialId.evaluate(&a), Aov::MaterialId.evaluate(&b));
+    }
+}
@@ -1,3 +1,9 @@
+pub mod aov;
+pub mod output;
 pub mod raytracer;
+pub mod tone_mapping;
 
+pub use aov::{Aov, RenderOutput};
+pub use output::{OutputFormat, save_aovs, save_image, save_multilayer_exr};
 pub use raytracer::Raytracer;
+pub use tone_mapping::{ToneMapper, ToneMapping};
@@ -0,0 +1,170 @@
+use crate::renderer::aov::Aov;
+use crate::renderer::tone_mapping::ToneMapper;
+use crate::{RaystError, Result};
+use glam::Vec3;
+use image::{ImageFormat, Rgb, Rgb32FImage, RgbImage};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    OpenExr,
+    Pfm,
+    Ldr,
+}
+
+impl OutputFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("exr") => OutputFormat::OpenExr,
+            Some("pfm") => OutputFormat::Pfm,
+            _ => OutputFormat::Ldr,
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        *self != OutputFormat::Ldr
+    }
+}
+
+pub fn save_image<P: AsRef<Path>>(
+    image: &Rgb32FImage,
+    path: P,
+    tone_mapper: Option<&ToneMapper>,
+) -> Result<()> {
+    let path = path.as_ref();
+
+    match OutputFormat::from_path(path) {
+        OutputFormat::OpenExr => image.save_with_format(path, ImageFormat::OpenExr)?,
+        OutputFormat::Pfm => write_pfm(image, path)?,
+        OutputFormat::Ldr => to_ldr(image, tone_mapper).save(path)?,
+    }
+
+    Ok(())
+}
+
+pub fn to_ldr(image: &Rgb32FImage, tone_mapper: Option<&ToneMapper>) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let color = Vec3::from_array(image.get_pixel(x, y).0);
+        let color = tone_mapper.map_or(color, |tone_mapper| tone_mapper.apply(color));
+
+        Rgb(color.to_array().map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8))
+    })
+}
+
+pub fn write_pfm<P: AsRef<Path>>(image: &Rgb32FImage, path: P) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    // A negative scale marks little-endian data; rows are stored bottom to top.
+    write!(writer, "PF\n{} {}\n-1.0\n", image.width(), image.height())?;
+
+    for y in (0..image.height()).rev() {
+        for x in 0..image.width() {
+            for channel in image.get_pixel(x, y).0 {
+                writer.write_all(&channel.to_le_bytes())?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn aov_path<P: AsRef<Path>>(path: P, aov: Aov) -> PathBuf {
+    let path = path.as_ref();
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("render");
+
+    // Data passes next to an 8-bit beauty image are written as EXR instead
+    let extension = if !aov.is_color() && !OutputFormat::from_path(path).is_hdr() {
+        Some("exr")
+    } else {
+        path.extension().and_then(|e| e.to_str())
+    };
+
+    let file_name = match extension {
+        Some(extension) => format!("{}.{}.{}", stem, aov.name(), extension),
+        None => format!("{}.{}", stem, aov.name()),
+    };
+
+    path.with_file_name(file_name)
+}
+
+pub fn save_aovs<P: AsRef<Path>>(aovs: &[(Aov, Rgb32FImage)], path: P) -> Result<Vec<PathBuf>> {
+    aovs.iter()
+        .map(|(aov, image)| {
+            let aov_path = aov_path(&path, *aov);
+            save_image(image, &aov_path, None)?;
+            Ok(aov_path)
+        })
+        .collect()
+}
+
+pub fn save_multilayer_exr<P: AsRef<Path>>(
+    beauty: &Rgb32FImage,
+    aovs: &[(Aov, Rgb32FImage)],
+    path: P,
+) -> Result<()> {
+    use exr::prelude::*;
+
+    let size = (beauty.width() as usize, beauty.height() as usize);
+
+    let channel = |name: String, image: &Rgb32FImage, index: usize| {
+        let samples = image.pixels().map(|pixel| pixel.0[index]).collect();
+        AnyChannel::new(name.as_str(), FlatSamples::F32(samples))
+    };
+
+    let mut channels: Vec<AnyChannel<FlatSamples>> = ["R", "G", "B"]
+        .iter()
+        .enumerate()
+        .map(|(index, name)| channel(name.to_string(), beauty, index))
+        .collect();
+
+    for (aov, image) in aovs {
+        for (index, name) in aov.channels().iter().enumerate() {
+            channels.push(channel(format!("{}.{}", aov.name(), name), image, index));
+        }
+    }
+
+    let layer = Layer::new(
+        size,
+        LayerAttributes::named("rayst"),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(SmallVec::from_vec(channels)),
+    );
+
+    Image::from_layer(layer)
+        .write()
+        .to_file(path.as_ref())
+        .map_err(|e| RaystError::RenderError(format!("Failed to write EXR: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_aovs_stay_hdr_next_to_ldr_output() {
+        assert_eq!(
+            aov_path("out/scene.png", Aov::Depth),
+            PathBuf::from("out/scene.depth.exr")
+        );
+        assert_eq!(
+            aov_path("out/scene.png", Aov::Albedo),
+            PathBuf::from("out/scene.albedo.png")
+        );
+        assert_eq!(
+            aov_path("out/scene.pfm", Aov::Normal),
+            PathBuf::from("out/scene.normal.pfm")
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use glam::{Mat3, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapping {
+    Clamp,
+    #[default]
+    Reinhard,
+    ReinhardExtended {
+        white_point: f32,
+    },
+    Aces,
+    AgX,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ToneMapper {
+    pub operator: ToneMapping,
+    pub exposure: f32,
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapping, exposure: f32) -> Self {
+        Self { operator, exposure }
+    }
+
+    pub fn apply(&self, color: Vec3) -> Vec3 {
+        let exposed = (color * self.exposure.exp2()).max(Vec3::ZERO);
+
+        let mapped = match self.operator {
+            ToneMapping::Clamp => exposed,
+            ToneMapping::Reinhard => exposed / (exposed + Vec3::ONE),
+            ToneMapping::ReinhardExtended { white_point } => {
+                let white_squared = (white_point * white_point).max(f32::EPSILON);
+                exposed * (Vec3::ONE + exposed / white_squared) / (exposed + Vec3::ONE)
+            }
+            ToneMapping::Aces => aces_fitted(exposed),
+            ToneMapping::AgX => agx(exposed),
+        };
+
+        mapped.clamp(Vec3::ZERO, Vec3::ONE).map(linear_to_srgb)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Stephen Hill's fit of the ACES RRT and sRGB ODT.
+fn aces_fitted(color: Vec3) -> Vec3 {
+    let input = Mat3::from_cols_array_2d(&[
+        [0.59719, 0.35458, 0.04823],
+        [0.07600, 0.90834, 0.01566],
+        [0.02840, 0.13383, 0.83777],
+    ])
+    .transpose();
+    let output = Mat3::from_cols_array_2d(&[
+        [1.60475, -0.53108, -0.07367],
+        [-0.10208, 1.10813, -0.00605],
+        [-0.00327, -0.07276, 1.07602],
+    ])
+    .transpose();
+
+    let v = input * color;
+    let a = v * (v + Vec3::splat(0.0245786)) - Vec3::splat(0.000090537);
+    let b = v * (0.983729 * v + Vec3::splat(0.432951)) + Vec3::splat(0.238081);
+
+    output * (a / b)
+}
+
+// Minimal AgX with the default look, using Benjamin Wrensch's polynomial fit
+// of the sigmoid. The result is converted back to linear for the sRGB encode.
+fn agx(color: Vec3) -> Vec3 {
+    const MIN_EV: f32 = -12.47393;
+    const MAX_EV: f32 = 4.026069;
+
+    let inset = Mat3::from_cols_array(&[
+        0.84247906,
+        0.042328242,
+        0.042375655,
+        0.0784336,
+        0.87846864,
+        0.0784336,
+        0.079223745,
+        0.07916613,
+        0.879143,
+    ]);
+    let outset = Mat3::from_cols_array(&[
+        1.196879,
+        -0.052896852,
+        -0.052971636,
+        -0.09802088,
+        1.1519031,
+        -0.09804345,
+        -0.09902974,
+        -0.098961177,
+        1.1510737,
+    ]);
+
+    let v = (inset * color).max(Vec3::splat(1e-10));
+    let v = (v
+        .map(f32::log2)
+        .clamp(Vec3::splat(MIN_EV), Vec3::splat(MAX_EV))
+        - MIN_EV)
+        / (MAX_EV - MIN_EV);
+
+    let v2 = v * v;
+    let v4 = v2 * v2;
+    let v =
+        15.5 * v4 * v2 - 40.14 * v4 * v + 31.96 * v4 - 6.868 * v2 * v + 0.4298 * v2 + 0.1191 * v
+            - Vec3::splat(0.00232);
+
+    (outset * v).max(Vec3::ZERO).powf(2.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Maps a gray input and checks every channel of the sRGB encoded result
+    fn assert_maps(operator: ToneMapping, pairs: &[(f32, f32)]) {
+        let mapper = ToneMapper::new(operator, 0.0);
+        for &(input, expected) in pairs {
+            let mapped = mapper.apply(Vec3::splat(input));
+            assert!(
+                mapped.abs_diff_eq(Vec3::splat(expected), 1e-3),
+                "{:?} maps {} to {}, expected {}",
+                operator,
+                input,
+                mapped,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn srgb_transfer_function() {
+        for (linear, encoded) in [
+            (0.0, 0.0),
+            (0.0031308, 0.04045),
+            (0.18, 0.461356),
+            (0.5, 0.735357),
+            (1.0, 1.0),
+        ] {
+            assert!((linear_to_srgb(linear) - encoded).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn reinhard_operators() {
+        assert_maps(
+            ToneMapping::Clamp,
+            &[(0.0, 0.0), (0.5, 0.735357), (1000.0, 1.0)],
+        );
+        assert_maps(
+            ToneMapping::Reinhard,
+            &[(0.0, 0.0), (1.0, 0.735357), (1000.0, 0.999561)],
+        );
+        // The white point maps to exactly 1
+        assert_maps(
+            ToneMapping::ReinhardExtended { white_point: 4.0 },
+            &[(0.0, 0.0), (1.0, 0.755576), (4.0, 1.0), (1000.0, 1.0)],
+        );
+    }
+
+    #[test]
+    fn filmic_operators() {
+        assert_maps(
+            ToneMapping::Aces,
+            &[(0.0, 0.0), (0.18, 0.358457), (1.0, 0.808954), (1000.0, 1.0)],
+        );
+        assert_maps(
+            ToneMapping::AgX,
+            &[
+                (0.0, 0.0),
+                (0.18, 0.500509),
+                (1.0, 0.791867),
+                (1000.0, 0.998605),
+            ],
+        );
+    }
+
+    #[test]
+    fn exposure_scales_in_stops() {
+        let brighter = ToneMapper::new(ToneMapping::Reinhard, 1.0).apply(Vec3::splat(0.5));
+        let reference = ToneMapper::new(ToneMapping::Reinhard, 0.0).apply(Vec3::ONE);
+        assert!(brighter.abs_diff_eq(reference, 1e-6));
+    }
+}
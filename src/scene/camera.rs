@@ -1,23 +1,106 @@
-use crate::math::Ray;
-use glam::Vec3;
+use crate::error::{RaystError, Result};
+use crate::math::{Ray, sampling};
+use crate::scene::projection::{Perspective, Projection};
+use glam::{Quat, Vec2, Vec3};
 
 pub struct Camera {
     pub position: Vec3,
     pub direction: Vec3,
     pub up: Vec3,
-    pub fov: f32,
+    pub right: Vec3,
+    pub projection: Box<dyn Projection>,
+    pub aperture: f32,
+    pub focus_distance: f32,
+    pub blades: u32,
+    pub blade_rotation: f32,
 }
 
 impl Camera {
     pub fn new(position: Vec3, direction: Vec3, fov: f32) -> Self {
+        let direction = direction.normalize();
+        let right = direction.cross(Self::default_up(direction)).normalize();
+
         Self {
             position,
-            direction: direction.normalize(),
-            up: Vec3::new(0.0, 1.0, 0.0),
-            fov,
+            direction,
+            up: right.cross(direction),
+            right,
+            projection: Box::new(Perspective::new(fov)),
+            aperture: 0.0,
+            focus_distance: 1.0,
+            blades: 0,
+            blade_rotation: 0.0,
+        }
+    }
+
+    // Looking straight up or down leaves +Y parallel to the view direction,
+    // so fall back to the horizontal up vector that a view tilted slightly
+    // towards -Z converges to, keeping the image from flipping at vertical.
+    pub fn default_up(direction: Vec3) -> Vec3 {
+        if direction.cross(Vec3::Y).length_squared() < 1e-8 * direction.length_squared() {
+            Vec3::new(0.0, 0.0, direction.y.signum())
+        } else {
+            Vec3::Y
         }
     }
 
+    pub fn oriented(
+        position: Vec3,
+        direction: Vec3,
+        up: Vec3,
+        roll: f32,
+        fov: f32,
+    ) -> Result<Self> {
+        if direction.length_squared() < 1e-12 {
+            return Err(RaystError::InvalidInput(String::from(
+                "Camera direction must not be zero",
+            )));
+        }
+        if up.length_squared() < 1e-12 {
+            return Err(RaystError::InvalidInput(String::from(
+                "Camera up vector must not be zero",
+            )));
+        }
+
+        let direction = direction.normalize();
+        let right = direction.cross(up.normalize());
+
+        if right.length_squared() < 1e-8 {
+            return Err(RaystError::InvalidInput(String::from(
+                "Camera up vector must not be parallel to the view direction",
+            )));
+        }
+
+        let roll = Quat::from_axis_angle(direction, -roll.to_radians());
+        let right = roll * right.normalize();
+
+        let mut camera = Self::new(position, direction, fov);
+        camera.right = right;
+        camera.up = right.cross(direction);
+
+        Ok(camera)
+    }
+
+    pub fn focus_on(&mut self, point: Vec3) {
+        self.focus_distance = (point - self.position).dot(self.direction).max(1e-3);
+    }
+
+    fn sample_lens(&self) -> Vec2 {
+        let sample = if self.blades >= 3 {
+            sampling::sample_regular_polygon(
+                fastrand::f32(),
+                fastrand::f32(),
+                fastrand::f32(),
+                self.blades,
+                self.blade_rotation.to_radians(),
+            )
+        } else {
+            sampling::sample_concentric_disk(fastrand::f32(), fastrand::f32())
+        };
+
+        sample * self.aperture
+    }
+
     pub fn generate_ray(
         &self,
         x: u32,
@@ -26,21 +109,58 @@ impl Camera {
         height: u32,
         offset_x: f32,
         offset_y: f32,
-    ) -> Ray {
+    ) -> Option<Ray> {
         let aspect_ratio = width as f32 / height as f32;
-        let fov_scale = (self.fov.to_radians() / 2.0).tan();
 
         let u = (x as f32 + offset_x) / width as f32 * 2.0 - 1.0;
         let v = 1.0 - (y as f32 + offset_y) / height as f32 * 2.0;
 
-        let u = u * aspect_ratio * fov_scale;
-        let v = v * fov_scale;
+        let (origin, direction) = self
+            .projection
+            .project(Vec2::new(u * aspect_ratio, v), aspect_ratio)?;
+
+        let origin = self.to_world(origin) + self.position;
+        let direction = self.to_world(direction).normalize();
+
+        if self.aperture <= 0.0 || !self.projection.supports_depth_of_field() {
+            return Some(Ray::new(origin, direction));
+        }
+
+        let focus_point =
+            origin + direction * (self.focus_distance / direction.dot(self.direction));
+        let lens = self.sample_lens();
+        let origin = origin + self.right * lens.x + self.up * lens.y;
 
-        let right = self.direction.cross(self.up).normalize();
-        let up = right.cross(self.direction).normalize();
+        Some(Ray::new(origin, (focus_point - origin).normalize()))
+    }
 
-        let direction = (self.direction + u * right + v * up).normalize();
+    fn to_world(&self, local: Vec3) -> Vec3 {
+        self.right * local.x + self.up * local.y + self.direction * local.z
+    }
+}
 
-        Ray::new(self.position, direction)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertical_view_matches_a_nearly_vertical_one() {
+        for vertical in [-1.0, 1.0] {
+            let orient = |direction: Vec3| {
+                Camera::oriented(
+                    Vec3::ZERO,
+                    direction,
+                    Camera::default_up(direction),
+                    0.0,
+                    60.0,
+                )
+                .unwrap()
+            };
+            let straight = orient(Vec3::new(0.0, vertical, 0.0));
+            let tilted = orient(Vec3::new(0.0, vertical, -1e-3));
+
+            assert!(straight.right.abs_diff_eq(tilted.right, 1e-3));
+            assert!(straight.up.abs_diff_eq(tilted.up, 1e-3));
+        }
     }
 }
@@ -1,17 +1,25 @@
 pub mod camera;
+pub mod projection;
 
 pub use camera::Camera;
+pub use projection::{
+    Equirectangular, Fisheye, FisheyeMapping, Orthographic, Perspective, Projection,
+};
 
 use crate::config::SceneConfig;
-use crate::geometry::Primitive;
-use crate::lighting::PointLight;
+use crate::geometry::{Bvh, Primitive};
+use crate::lighting::{EnvironmentMap, Light};
 use glam::Vec3;
 
 pub struct Scene {
     pub objects: Vec<Box<dyn Primitive>>,
-    pub lights: Vec<PointLight>,
+    pub lights: Vec<Box<dyn Light>>,
     pub background_color: Vec3,
+    pub environment: Option<EnvironmentMap>,
     pub ambient_light: f32,
+    bvh: Option<Bvh>,
+    unbounded: Vec<usize>,
+    emissive: Vec<usize>,
 }
 
 impl Scene {
@@ -19,33 +27,230 @@ impl Scene {
         Self {
             objects: Vec::new(),
             lights: Vec::new(),
-            background_color: Vec3::from_array(config.background.color.map(|c| c as f32)),
+            // Radiance in the same linear units as emission and environment
+            // maps, scaled by the same intensity
+            background_color: Vec3::from_array(
+                config
+                    .background
+                    .color
+                    .unwrap_or([0, 0, 0])
+                    .map(|c| c as f32 / 255.0),
+            ) * config.background.intensity.unwrap_or(1.0),
+            environment: None,
             ambient_light: 0.1,
+            bvh: None,
+            unbounded: Vec::new(),
+            emissive: Vec::new(),
         }
     }
 
     pub fn add_object(&mut self, object: Box<dyn Primitive>) {
+        if object.is_emissive() {
+            self.emissive.push(self.objects.len());
+        }
         self.objects.push(object);
+        self.bvh = None;
     }
 
-    pub fn add_light(&mut self, light: PointLight) {
+    pub fn add_light(&mut self, light: Box<dyn Light>) {
         self.lights.push(light);
     }
 
+    pub fn emissive_objects(&self) -> impl Iterator<Item = &dyn Primitive> {
+        self.emissive
+            .iter()
+            .map(|&index| self.objects[index].as_ref())
+    }
+
+    pub fn emissive_object(&self, index: usize) -> Option<&dyn Primitive> {
+        self.emissive
+            .get(index)
+            .map(|&index| self.objects[index].as_ref())
+    }
+
+    pub fn build_bvh(&mut self) {
+        self.unbounded = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.bounding_box().is_none())
+            .map(|(index, _)| index)
+            .collect();
+        self.bvh = Some(Bvh::new(&self.objects));
+    }
+
+    // The environment map, when present, counts as the last light
+    pub fn light_count(&self) -> usize {
+        self.lights.len() + self.emissive.len() + usize::from(self.environment.is_some())
+    }
+
+    pub fn background(&self, direction: Vec3) -> Vec3 {
+        self.environment
+            .as_ref()
+            .map_or(self.background_color, |environment| {
+                environment.radiance(direction)
+            })
+    }
+
     pub fn hit(&self, ray: &crate::math::Ray) -> Option<crate::math::Intersection> {
-        let mut closest_hit: Option<crate::math::Intersection> = None;
+        let (object_id, mut hit) = match &self.bvh {
+            Some(bvh) => self.hit_bvh(bvh, ray),
+            None => self.hit_linear(ray),
+        }?;
+
+        hit.object_id = object_id;
+        if hit.material.is_textured() {
+            hit.material.apply_textures(hit.uv, hit.point);
+            hit.shading_normal = hit.material.shading_normal(&hit);
+        }
+        hit.base_albedo = hit.material.albedo;
+        if hit.color != Vec3::ONE {
+            hit.material.albedo *= hit.color;
+        }
+        Some(hit)
+    }
+
+    fn hit_bvh(
+        &self,
+        bvh: &Bvh,
+        ray: &crate::math::Ray,
+    ) -> Option<(usize, crate::math::Intersection)> {
+        let mut closest_hit = bvh.hit(&self.objects, ray);
+        let mut closest_distance = closest_hit
+            .as_ref()
+            .map_or(f32::INFINITY, |(_, hit)| hit.distance);
+
+        for &index in &self.unbounded {
+            if let Some(hit) = self.objects[index].hit(ray)
+                && hit.distance < closest_distance
+                && hit.distance > 0.001
+            {
+                closest_distance = hit.distance;
+                closest_hit = Some((index, hit));
+            }
+        }
+
+        closest_hit
+    }
+
+    fn hit_linear(&self, ray: &crate::math::Ray) -> Option<(usize, crate::math::Intersection)> {
+        let mut closest_hit: Option<(usize, crate::math::Intersection)> = None;
         let mut closest_distance = f32::INFINITY;
 
-        for sphere in &self.objects {
-            if let Some(hit) = sphere.hit(ray)
+        for (index, object) in self.objects.iter().enumerate() {
+            if let Some(hit) = object.hit(ray)
                 && hit.distance < closest_distance
                 && hit.distance > 0.001
             {
                 closest_distance = hit.distance;
-                closest_hit = Some(hit);
+                closest_hit = Some((index, hit));
             }
         }
 
         closest_hit
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Plane, Sphere};
+    use crate::materials::Material;
+    use crate::math::Ray;
+
+    fn scene() -> Scene {
+        let config: SceneConfig = toml::from_str(
+            r#"
+            objects = []
+
+            [camera]
+            position = [0.0, 0.0, 0.0]
+            fov = 60.0
+
+            [render]
+            width = 1
+            height = 1
+            max_depth = 1
+            antialiasing = 1
+            output_file = "out.png"
+
+            [background]
+            "#,
+        )
+        .unwrap();
+        Scene::new(&config)
+    }
+
+    #[test]
+    fn bvh_and_linear_search_agree_with_unbounded_objects() {
+        fastrand::seed(5);
+        let mut scene = scene();
+        let material = Material::new(Vec3::splat(0.8), 0.0, 0.5);
+        for _ in 0..100 {
+            let center = Vec3::new(fastrand::f32(), fastrand::f32(), fastrand::f32()) * 20.0
+                - Vec3::splat(10.0);
+            scene.add_object(Box::new(Sphere::new(center, 0.5, material.clone())));
+        }
+        scene.add_object(Box::new(Plane::new(-Vec3::Y * 5.0, Vec3::Y, material)));
+        scene.build_bvh();
+        let bvh = scene.bvh.clone().unwrap();
+
+        for _ in 0..1000 {
+            let direction =
+                Vec3::new(fastrand::f32(), fastrand::f32(), fastrand::f32()) * 2.0 - Vec3::ONE;
+            let ray = Ray::new(Vec3::ZERO, direction.normalize_or(Vec3::X));
+            let distance = |hit: Option<(usize, crate::math::Intersection)>| {
+                hit.map(|(index, hit)| (index, hit.distance))
+            };
+            assert_eq!(
+                distance(scene.hit_bvh(&bvh, &ray)),
+                distance(scene.hit_linear(&ray))
+            );
+        }
+    }
+
+    #[test]
+    fn flat_background_matches_an_equally_bright_environment() {
+        let mut config: SceneConfig = toml::from_str(
+            r#"
+            objects = []
+
+            [camera]
+            position = [0.0, 0.0, 0.0]
+            fov = 60.0
+
+            [render]
+            width = 1
+            height = 1
+            max_depth = 1
+            antialiasing = 1
+            output_file = "out.png"
+
+            [background]
+            color = [255, 51, 0]
+            intensity = 2.0
+            "#,
+        )
+        .unwrap();
+        let flat = Scene::new(&config);
+
+        config.background.color = None;
+        let mut mapped = Scene::new(&config);
+        let pixels = vec![Vec3::new(1.0, 0.2, 0.0); 8];
+        mapped.environment = Some(EnvironmentMap::new(pixels, 4, 2, 0.0, 2.0).unwrap());
+
+        for direction in [Vec3::Y, -Vec3::Z, Vec3::new(1.0, -1.0, 0.5).normalize()] {
+            assert!(
+                flat.background(direction)
+                    .abs_diff_eq(mapped.background(direction), 1e-6)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_scene_with_a_bvh_misses() {
+        let mut scene = scene();
+        scene.build_bvh();
+        assert!(scene.hit(&Ray::new(Vec3::ZERO, -Vec3::Z)).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use glam::{Vec2, Vec3};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+// Maps a film position (x in [-aspect, aspect], y in [-1, 1], +y up) to a
+// camera space ray origin and direction (+X right, +Y up, +Z forward).
+// Returns None for film positions the projection does not cover.
+pub trait Projection: Send + Sync {
+    fn project(&self, film: Vec2, aspect_ratio: f32) -> Option<(Vec3, Vec3)>;
+
+    fn supports_depth_of_field(&self) -> bool {
+        false
+    }
+}
+
+pub struct Perspective {
+    pub fov: f32,
+}
+
+impl Perspective {
+    pub fn new(fov: f32) -> Self {
+        Self { fov }
+    }
+}
+
+impl Projection for Perspective {
+    fn project(&self, film: Vec2, _aspect_ratio: f32) -> Option<(Vec3, Vec3)> {
+        let scale = (self.fov.to_radians() / 2.0).tan();
+        let direction = Vec3::new(film.x * scale, film.y * scale, 1.0).normalize();
+
+        Some((Vec3::ZERO, direction))
+    }
+
+    fn supports_depth_of_field(&self) -> bool {
+        true
+    }
+}
+
+pub struct Orthographic {
+    pub width: f32,
+}
+
+impl Orthographic {
+    pub fn new(width: f32) -> Self {
+        Self { width }
+    }
+}
+
+impl Projection for Orthographic {
+    fn project(&self, film: Vec2, aspect_ratio: f32) -> Option<(Vec3, Vec3)> {
+        let half_width = self.width / 2.0;
+        let origin = (film / aspect_ratio * half_width).extend(0.0);
+
+        Some((origin, Vec3::Z))
+    }
+
+    fn supports_depth_of_field(&self) -> bool {
+        true
+    }
+}
+
+// Full 360x180 degree panorama, longitude across and latitude down the image
+pub struct Equirectangular;
+
+impl Projection for Equirectangular {
+    fn project(&self, film: Vec2, aspect_ratio: f32) -> Option<(Vec3, Vec3)> {
+        let phi = film.x / aspect_ratio * PI;
+        let theta = film.y * FRAC_PI_2;
+
+        let direction = Vec3::new(
+            theta.cos() * phi.sin(),
+            theta.sin(),
+            theta.cos() * phi.cos(),
+        );
+
+        Some((Vec3::ZERO, direction))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    Equidistant,
+    Equisolid,
+}
+
+// Circular fisheye whose image circle spans the image height
+pub struct Fisheye {
+    pub fov: f32,
+    pub mapping: FisheyeMapping,
+}
+
+impl Fisheye {
+    pub fn new(fov: f32, mapping: FisheyeMapping) -> Self {
+        Self { fov, mapping }
+    }
+}
+
+impl Projection for Fisheye {
+    fn project(&self, film: Vec2, _aspect_ratio: f32) -> Option<(Vec3, Vec3)> {
+        let r = film.length();
+        if r > 1.0 {
+            return None;
+        }
+
+        let half_fov = self.fov.to_radians() / 2.0;
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => r * half_fov,
+            FisheyeMapping::Equisolid => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+        };
+
+        let radial = if r > 0.0 { film / r } else { Vec2::ZERO };
+        let direction = (radial * theta.sin()).extend(theta.cos());
+
+        Some((Vec3::ZERO, direction))
+    }
+}
@@ -0,0 +1,36 @@
+use crate::textures::Texture;
+use crate::textures::texture::hash_vec3;
+use glam::{Vec2, Vec3};
+use std::hash::{Hash, Hasher};
+
+// Alternating squares in UV space, `scale` squares per UV unit
+#[derive(Debug, Clone)]
+pub struct CheckerTexture {
+    pub even: Vec3,
+    pub odd: Vec3,
+    pub scale: f32,
+}
+
+impl CheckerTexture {
+    pub fn new(even: Vec3, odd: Vec3, scale: f32) -> Self {
+        Self { even, odd, scale }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, uv: Vec2, _point: Vec3) -> Vec3 {
+        let cell = (uv * self.scale).floor();
+        if (cell.x + cell.y).rem_euclid(2.0) < 1.0 {
+            self.even
+        } else {
+            self.odd
+        }
+    }
+
+    fn identity(&self, mut state: &mut dyn Hasher) {
+        "checker".hash(&mut state);
+        hash_vec3(self.even, state);
+        hash_vec3(self.odd, state);
+        self.scale.to_bits().hash(&mut state);
+    }
+}
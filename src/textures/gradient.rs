@@ -0,0 +1,46 @@
+use crate::textures::texture::hash_vec3;
+use crate::textures::{Texture, WrapMode};
+use glam::{Vec2, Vec3};
+use std::hash::{Hash, Hasher};
+
+// Linear blend from `start` to `end` along `direction` in UV space
+#[derive(Debug, Clone)]
+pub struct GradientTexture {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub direction: Vec2,
+    pub wrap: WrapMode,
+}
+
+impl GradientTexture {
+    pub fn new(start: Vec3, end: Vec3, direction: Vec2) -> Self {
+        Self {
+            start,
+            end,
+            direction,
+            wrap: WrapMode::Clamp,
+        }
+    }
+}
+
+impl Texture for GradientTexture {
+    fn value(&self, uv: Vec2, _point: Vec3) -> Vec3 {
+        let t = uv.dot(self.direction);
+        let t = match self.wrap {
+            WrapMode::Repeat => t.rem_euclid(1.0),
+            WrapMode::Mirror => 1.0 - (t.rem_euclid(2.0) - 1.0).abs(),
+            WrapMode::Clamp => t.clamp(0.0, 1.0),
+        };
+
+        self.start.lerp(self.end, t)
+    }
+
+    fn identity(&self, mut state: &mut dyn Hasher) {
+        "gradient".hash(&mut state);
+        hash_vec3(self.start, state);
+        hash_vec3(self.end, state);
+        self.direction.x.to_bits().hash(&mut state);
+        self.direction.y.to_bits().hash(&mut state);
+        self.wrap.hash(&mut state);
+    }
+}
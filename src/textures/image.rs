@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::textures::{Texture, WrapMode};
+use glam::{Vec2, Vec3};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vec3>,
+    pub wrap: WrapMode,
+    pub scale: Vec2,
+    // Hash of the pixel data, taken once so identifying the texture per hit
+    // stays cheap
+    fingerprint: u64,
+}
+
+impl ImageTexture {
+    pub fn new(pixels: Vec<Vec3>, width: usize, height: usize) -> Self {
+        let mut hasher = DefaultHasher::new();
+        for pixel in &pixels {
+            for component in pixel.to_array() {
+                component.to_bits().hash(&mut hasher);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+            fingerprint: hasher.finish(),
+            wrap: WrapMode::default(),
+            scale: Vec2::ONE,
+        }
+    }
+
+    // 8 and 16 bit images are sRGB encoded unless `linear` is set; float
+    // images such as EXR are always linear
+    pub fn load_from_file<P: AsRef<Path>>(path: P, linear: bool) -> Result<Self> {
+        let image = image::open(path.as_ref())?;
+        let is_float = matches!(
+            image.color(),
+            image::ColorType::Rgb32F | image::ColorType::Rgba32F
+        );
+        let image = image.into_rgb32f();
+        let (width, height) = image.dimensions();
+
+        let pixels = image
+            .pixels()
+            .map(|p| {
+                let color = Vec3::from_array(p.0);
+                if linear || is_float {
+                    color
+                } else {
+                    Vec3::new(
+                        srgb_to_linear(color.x),
+                        srgb_to_linear(color.y),
+                        srgb_to_linear(color.z),
+                    )
+                }
+            })
+            .collect();
+
+        Ok(Self::new(pixels, width as usize, height as usize))
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = self.wrap.apply(x, self.width);
+        let y = self.wrap.apply(y, self.height);
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, uv: Vec2, _point: Vec3) -> Vec3 {
+        if self.pixels.is_empty() {
+            return Vec3::ZERO;
+        }
+
+        // v runs up the image, texel centers sit at half-integer coordinates
+        let uv = uv * self.scale;
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = (1.0 - uv.y) * self.height as f32 - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0).lerp(self.texel(x0 + 1, y0), tx);
+        let bottom = self.texel(x0, y0 + 1).lerp(self.texel(x0 + 1, y0 + 1), tx);
+
+        top.lerp(bottom, ty)
+    }
+
+    fn identity(&self, mut state: &mut dyn Hasher) {
+        "image".hash(&mut state);
+        self.width.hash(&mut state);
+        self.height.hash(&mut state);
+        self.fingerprint.hash(&mut state);
+        self.wrap.hash(&mut state);
+        self.scale.x.to_bits().hash(&mut state);
+        self.scale.y.to_bits().hash(&mut state);
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
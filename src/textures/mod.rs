@@ -0,0 +1,11 @@
+pub mod checker;
+pub mod gradient;
+pub mod image;
+pub mod noise;
+pub mod texture;
+
+pub use checker::CheckerTexture;
+pub use gradient::GradientTexture;
+pub use image::ImageTexture;
+pub use noise::NoiseTexture;
+pub use texture::{Texture, WrapMode};
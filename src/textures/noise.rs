@@ -0,0 +1,104 @@
+use crate::textures::Texture;
+use crate::textures::texture::hash_vec3;
+use glam::{Vec2, Vec3};
+use std::hash::{Hash, Hasher};
+
+// Solid fractal Brownian motion over Perlin gradient noise, evaluated at the
+// world space hit point and blended between `low` and `high`
+#[derive(Debug, Clone)]
+pub struct NoiseTexture {
+    pub low: Vec3,
+    pub high: Vec3,
+    pub scale: f32,
+    pub octaves: u32,
+    permutation: Vec<u8>,
+}
+
+impl NoiseTexture {
+    pub fn new(low: Vec3, high: Vec3, scale: f32, octaves: u32, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let mut permutation: Vec<u8> = (0..=255).collect();
+        rng.shuffle(&mut permutation);
+
+        Self {
+            low,
+            high,
+            scale,
+            octaves: octaves.max(1),
+            permutation,
+        }
+    }
+
+    pub fn fbm(&self, point: Vec3) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = 1.0;
+
+        for _ in 0..self.octaves {
+            sum += amplitude * self.perlin(point * frequency);
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        sum
+    }
+
+    fn hash(&self, x: i32, y: i32, z: i32) -> u8 {
+        let p = |i: i32| self.permutation[(i & 255) as usize] as i32;
+        p(p(p(x) + y) + z) as u8
+    }
+
+    fn perlin(&self, point: Vec3) -> f32 {
+        let cell = point.floor();
+        let f = point - cell;
+        let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+        let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            let offset = f - Vec3::new(dx as f32, dy as f32, dz as f32);
+            gradient(self.hash(x + dx, y + dy, z + dz), offset)
+        };
+
+        let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fade.x);
+        let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fade.x);
+        let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fade.x);
+        let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fade.x);
+
+        lerp(lerp(x00, x10, fade.y), lerp(x01, x11, fade.y), fade.z)
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _uv: Vec2, point: Vec3) -> Vec3 {
+        let t = (0.5 + self.fbm(point * self.scale)).clamp(0.0, 1.0);
+        self.low.lerp(self.high, t)
+    }
+
+    fn identity(&self, mut state: &mut dyn Hasher) {
+        "noise".hash(&mut state);
+        hash_vec3(self.low, state);
+        hash_vec3(self.high, state);
+        self.scale.to_bits().hash(&mut state);
+        self.octaves.hash(&mut state);
+        self.permutation.hash(&mut state);
+    }
+}
+
+// Improved Perlin noise gradients: the 12 cube edge directions
+fn gradient(hash: u8, offset: Vec3) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { offset.x } else { offset.y };
+    let v = if h < 4 {
+        offset.y
+    } else if h == 12 || h == 14 {
+        offset.x
+    } else {
+        offset.z
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
@@ -0,0 +1,56 @@
+use glam::{Vec2, Vec3};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+pub trait Texture: Send + Sync + Debug {
+    // `uv` comes from the surface parameterization, `point` is the world
+    // space hit point used by solid textures
+    fn value(&self, uv: Vec2, point: Vec3) -> Vec3;
+
+    // Feeds the parameters that define the texture into `state`, so equal
+    // textures hash alike from one run to the next
+    fn identity(&self, state: &mut dyn Hasher);
+}
+
+pub(crate) fn hash_vec3(value: Vec3, mut state: &mut dyn Hasher) {
+    for component in value.to_array() {
+        component.to_bits().hash(&mut state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl WrapMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "repeat" => Some(Self::Repeat),
+            "mirror" => Some(Self::Mirror),
+            "clamp" => Some(Self::Clamp),
+            _ => None,
+        }
+    }
+
+    // Maps a texel index to a valid index in 0..size
+    pub fn apply(&self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            Self::Repeat => index.rem_euclid(size),
+            Self::Mirror => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+            Self::Clamp => index.clamp(0, size - 1),
+        };
+        index as usize
+    }
+}